@@ -0,0 +1,115 @@
+use anyhow::Context;
+use directories::BaseDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The default earcons and the dev voicepack, by path under the data dir.
+pub const FILES: &[(&str, &[u8])] = &[
+    (
+        "earcons/decision.wav",
+        include_bytes!("../earcons/decision.wav"),
+    ),
+    ("earcons/error.wav", include_bytes!("../earcons/error.wav")),
+    ("earcons/yield.wav", include_bytes!("../earcons/yield.wav")),
+    (
+        "voicepack/manifest.json",
+        include_bytes!("../voicepack/manifest.json"),
+    ),
+    (
+        "voicepack/audio/agent_all_set.wav",
+        include_bytes!("../voicepack/audio/agent_all_set.wav"),
+    ),
+    (
+        "voicepack/audio/agent_done.wav",
+        include_bytes!("../voicepack/audio/agent_done.wav"),
+    ),
+    (
+        "voicepack/audio/agent_next_step.wav",
+        include_bytes!("../voicepack/audio/agent_next_step.wav"),
+    ),
+    (
+        "voicepack/audio/agent_ready.wav",
+        include_bytes!("../voicepack/audio/agent_ready.wav"),
+    ),
+    (
+        "voicepack/audio/agent_your_turn.wav",
+        include_bytes!("../voicepack/audio/agent_your_turn.wav"),
+    ),
+    (
+        "voicepack/audio/build_complete.wav",
+        include_bytes!("../voicepack/audio/build_complete.wav"),
+    ),
+    (
+        "voicepack/audio/decision_call.wav",
+        include_bytes!("../voicepack/audio/decision_call.wav"),
+    ),
+    (
+        "voicepack/audio/decision_choose.wav",
+        include_bytes!("../voicepack/audio/decision_choose.wav"),
+    ),
+    (
+        "voicepack/audio/decision_input.wav",
+        include_bytes!("../voicepack/audio/decision_input.wav"),
+    ),
+    (
+        "voicepack/audio/decision_question.wav",
+        include_bytes!("../voicepack/audio/decision_question.wav"),
+    ),
+    (
+        "voicepack/audio/deploy_complete.wav",
+        include_bytes!("../voicepack/audio/deploy_complete.wav"),
+    ),
+    (
+        "voicepack/audio/error_failed.wav",
+        include_bytes!("../voicepack/audio/error_failed.wav"),
+    ),
+    (
+        "voicepack/audio/error_hit.wav",
+        include_bytes!("../voicepack/audio/error_hit.wav"),
+    ),
+    (
+        "voicepack/audio/error_retry.wav",
+        include_bytes!("../voicepack/audio/error_retry.wav"),
+    ),
+    (
+        "voicepack/audio/error_timeout.wav",
+        include_bytes!("../voicepack/audio/error_timeout.wav"),
+    ),
+    (
+        "voicepack/audio/tests_failed.wav",
+        include_bytes!("../voicepack/audio/tests_failed.wav"),
+    ),
+];
+
+/// Default home for the installed assets: the XDG data dir
+/// (`~/.local/share/agent-chime` on Linux). `data_dir` in the config
+/// replaces it.
+pub fn default_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|base| base.data_dir().join("agent-chime"))
+}
+
+#[derive(Debug, Default)]
+pub struct InstallReport {
+    pub written: Vec<PathBuf>,
+    /// Files that already existed and were left alone.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Extracts the embedded assets into `dir`. Existing files are kept unless
+/// `force` is set, so edited earcons survive a reinstall.
+pub fn install(dir: &Path, force: bool) -> anyhow::Result<InstallReport> {
+    let mut report = InstallReport::default();
+    for (name, bytes) in FILES {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            report.skipped.push(path);
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        fs::write(&path, bytes).with_context(|| format!("write {}", path.display()))?;
+        report.written.push(path);
+    }
+    Ok(report)
+}
@@ -1,8 +1,10 @@
-use crate::config::{Config, Mode};
-use crate::events::EventType;
+use crate::config::{Config, Mode, ToneSpec, ValidationReport};
+use crate::events::{EventType, Priority};
 use anyhow::Context;
+use std::path::PathBuf;
 
-use super::renderer;
+use super::renderer::{self, Playback};
+use super::{tone, wav};
 
 pub fn should_play(event_type: EventType, config: &Config) -> bool {
     config
@@ -12,30 +14,82 @@ pub fn should_play(event_type: EventType, config: &Config) -> bool {
         .unwrap_or(false)
 }
 
-pub fn play_for_event(event_type: EventType, config: &Config) -> anyhow::Result<()> {
+pub fn play_for_event(
+    event_type: EventType,
+    config: &Config,
+    priority: Priority,
+) -> anyhow::Result<()> {
     if !should_play(event_type, config) {
         return Ok(());
     }
+    play_fallback(event_type, config, priority)
+}
 
-    let dir = match config.default_earcons_dir() {
-        Some(dir) => dir,
-        None => {
-            tracing::warn!("earcons directory not found; skipping earcon");
-            return Ok(());
+/// Plays the event's earcon whatever its mode, for when speech failed.
+pub fn play_fallback(
+    event_type: EventType,
+    config: &Config,
+    priority: Priority,
+) -> anyhow::Result<()> {
+    let playback = Playback::new(config, priority);
+    match resolve(event_type, config) {
+        Earcon::File(path) => renderer::play_file(&path, &playback).context("play earcon"),
+        Earcon::Tone(spec) => {
+            let audio = wav::encode(&tone::render(&spec))?;
+            renderer::play_bytes(&audio, &playback).context("play earcon")
         }
-    };
+    }
+}
+
+/// Where an event's earcon comes from.
+pub enum Earcon {
+    File(PathBuf),
+    Tone(ToneSpec),
+}
 
-    let filename = match event_type {
+/// A configured tone wins, then the event's WAV file in the earcons dir,
+/// then the built-in tone, so there is always something to play.
+pub fn resolve(event_type: EventType, config: &Config) -> Earcon {
+    if let Some(spec) = config.earcon_tones.get(&event_type) {
+        return Earcon::Tone(spec.clone());
+    }
+    if let Some(dir) = config.default_earcons_dir() {
+        let path = dir.join(filename(event_type));
+        if path.is_file() {
+            return Earcon::File(path);
+        }
+        tracing::debug!(path = %path.display(), "earcon file missing; using built-in tone");
+    }
+    Earcon::Tone(tone::default_spec(event_type))
+}
+
+pub fn filename(event_type: EventType) -> &'static str {
+    match event_type {
         EventType::AgentYield => "yield.wav",
         EventType::DecisionRequired => "decision.wav",
         EventType::ErrorRetry => "error.wav",
-    };
+    }
+}
 
-    let path = dir.join(filename);
-    if !path.exists() {
-        tracing::warn!(path = %path.display(), "earcon file missing; skipping");
-        return Ok(());
+/// Reports configured tones that can't be rendered, and an `earcons_dir`
+/// that was set but doesn't exist.
+pub fn check(config: &Config, report: &mut ValidationReport) {
+    if let Some(dir) = &config.earcons_dir {
+        if !dir.is_dir() {
+            report.push(
+                "earcons_dir",
+                format!("directory not found: {}", dir.display()),
+            );
+        }
     }
 
-    renderer::play_file(&path, config.volume).context("play earcon")
+    let mut tones: Vec<_> = config.earcon_tones.iter().collect();
+    tones.sort_by_key(|(event_type, _)| event_type.as_str());
+    for (event_type, spec) in tones {
+        tone::check(
+            &format!("earcon_tones.{}", event_type.as_str()),
+            spec,
+            report,
+        );
+    }
 }
@@ -1,3 +1,5 @@
 pub mod cache;
 pub mod earcon;
 pub mod renderer;
+pub mod tone;
+pub mod wav;
@@ -1,24 +1,310 @@
+use super::wav::{self, Pcm};
+use crate::config::Config;
+use crate::events::Priority;
+use crate::runtime;
 use anyhow::{bail, Context};
-use std::path::Path;
-use std::process::Command;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 
-pub fn play_file(path: &Path, volume: f32) -> anyhow::Result<()> {
-    let status = Command::new("afplay")
-        .arg("-v")
-        .arg(volume.to_string())
-        .arg(path)
-        .status()
-        .with_context(|| format!("play audio with afplay: {}", path.display()))?;
+const LOCK_NAME: &str = "playback.lock";
+const QUEUE_NAME: &str = "playback";
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// Waiters refresh their entry every poll; one not seen for this long
+/// belonged to a process that died.
+const STALE_MS: i64 = 2000;
 
-    if !status.success() {
-        bail!("afplay exited with status {status}");
+/// How one clip is played.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    pub volume: f32,
+    pub priority: Priority,
+    pub max_wait: Duration,
+    /// Stop a lower-priority clip that is playing instead of waiting for it.
+    pub preempt: bool,
+    /// Holds the queue shared with other `agent-chime` processes.
+    pub runtime_dir: PathBuf,
+}
+
+impl Playback {
+    pub fn new(config: &Config, priority: Priority) -> Self {
+        Self {
+            volume: config.volume,
+            priority,
+            max_wait: Duration::from_millis(config.playback.max_wait_ms),
+            preempt: config.playback.preempt,
+            runtime_dir: config.default_runtime_dir(),
+        }
+    }
+}
+
+/// Clips waiting for the speaker, plus whoever has it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Queue {
+    #[serde(default)]
+    next_ticket: u64,
+    #[serde(default)]
+    waiters: Vec<Waiter>,
+    #[serde(default)]
+    holder: Option<Holder>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Waiter {
+    ticket: u64,
+    priority: Priority,
+    /// Last heartbeat, in Unix milliseconds.
+    seen: i64,
+}
+
+/// The clip that has the speaker.
+#[derive(Debug, Serialize, Deserialize)]
+struct Holder {
+    ticket: u64,
+    priority: Priority,
+    /// Set by a higher-priority clip that wants the speaker. The holder
+    /// checks it while its player runs and stops the player itself.
+    #[serde(default)]
+    preempted: bool,
+}
+
+impl Waiter {
+    /// Higher priorities go first, then arrival order.
+    fn ahead_of(&self, other: &Waiter) -> bool {
+        self.priority > other.priority
+            || (self.priority == other.priority && self.ticket < other.ticket)
+    }
+}
+
+/// Holds the speaker until dropped. The OS releases the file lock if the
+/// process dies, so a crashed player never blocks the queue.
+pub struct PlaybackGuard {
+    _lock: File,
+    ticket: u64,
+    runtime_dir: PathBuf,
+}
+
+impl PlaybackGuard {
+    /// Waits for `player` to exit, killing it if a higher-priority clip
+    /// preempts this one. Returns `None` if it was stopped that way.
+    pub fn wait(&self, mut player: Child) -> anyhow::Result<Option<ExitStatus>> {
+        loop {
+            if let Some(status) = player.try_wait().context("wait for player")? {
+                return Ok(Some(status));
+            }
+            if self.preempted() {
+                let _ = player.kill();
+                player.wait().context("wait for player")?;
+                return Ok(None);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    pub fn preempted(&self) -> bool {
+        let queue: Queue = runtime::read(&self.runtime_dir, QUEUE_NAME);
+        queue
+            .holder
+            .is_some_and(|holder| holder.ticket == self.ticket && holder.preempted)
+    }
+}
+
+impl Drop for PlaybackGuard {
+    fn drop(&mut self) {
+        let ticket = self.ticket;
+        let _ = runtime::update(&self.runtime_dir, QUEUE_NAME, |queue: &mut Queue| {
+            if queue.holder.as_ref().is_some_and(|h| h.ticket == ticket) {
+                queue.holder = None;
+            }
+        });
+    }
+}
+
+/// Waits for this clip's turn at the speaker, stopping a lower-priority clip
+/// if `preempt` is set. Returns `None` when the clip should be dropped
+/// because it waited `max_wait` without getting the speaker.
+pub fn acquire(playback: &Playback) -> anyhow::Result<Option<PlaybackGuard>> {
+    let priority = playback.priority;
+    let dir = playback.runtime_dir.as_path();
+    let lock = runtime::open(dir, LOCK_NAME)?;
+    let ticket = runtime::update(dir, QUEUE_NAME, |queue: &mut Queue| {
+        queue.next_ticket += 1;
+        queue.waiters.push(Waiter {
+            ticket: queue.next_ticket,
+            priority,
+            seen: now_ms(),
+        });
+        queue.next_ticket
+    })?;
+
+    let deadline = Instant::now() + playback.max_wait;
+    loop {
+        let (my_turn, holder) = runtime::update(dir, QUEUE_NAME, |queue: &mut Queue| {
+            let now = now_ms();
+            queue
+                .waiters
+                .retain(|w| w.ticket == ticket || now - w.seen < STALE_MS);
+            let Some(me) = queue.waiters.iter_mut().find(|w| w.ticket == ticket) else {
+                return (false, None);
+            };
+            me.seen = now;
+            let me = Waiter {
+                ticket,
+                priority,
+                seen: now,
+            };
+            let my_turn = !queue.waiters.iter().any(|w| w.ahead_of(&me));
+            (my_turn, queue.holder.as_ref().map(|h| h.priority))
+        })?;
+
+        if my_turn {
+            match lock.try_lock() {
+                Ok(()) => {
+                    runtime::update(dir, QUEUE_NAME, |queue: &mut Queue| {
+                        queue.waiters.retain(|w| w.ticket != ticket);
+                        queue.holder = Some(Holder {
+                            ticket,
+                            priority,
+                            preempted: false,
+                        });
+                    })?;
+                    return Ok(Some(PlaybackGuard {
+                        _lock: lock,
+                        ticket,
+                        runtime_dir: dir.to_path_buf(),
+                    }));
+                }
+                Err(TryLockError::WouldBlock)
+                    if playback.preempt
+                        && holder.is_some_and(|h| priority > h)
+                        && Instant::now() < deadline =>
+                {
+                    preempt_holder(dir, priority);
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(err)) => {
+                    leave(dir, ticket);
+                    return Err(err).context("lock playback");
+                }
+            }
+        }
+
+        if Instant::now() >= deadline {
+            leave(dir, ticket);
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL);
     }
+}
+
+fn preempt_holder(dir: &Path, priority: Priority) {
+    let victim = runtime::update(dir, QUEUE_NAME, |queue: &mut Queue| {
+        let holder = queue.holder.as_mut()?;
+        if holder.preempted || holder.priority >= priority {
+            return None;
+        }
+        holder.preempted = true;
+        Some(holder.priority)
+    });
+    let Ok(Some(victim_priority)) = victim else {
+        return;
+    };
+    tracing::info!(
+        priority = priority.as_str(),
+        preempted = victim_priority.as_str(),
+        "preempting lower-priority playback"
+    );
+}
 
+fn leave(dir: &Path, ticket: u64) {
+    let _ = runtime::update(dir, QUEUE_NAME, |queue: &mut Queue| {
+        queue.waiters.retain(|w| w.ticket != ticket);
+    });
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Plays `path` once the clips queued ahead of it have finished.
+pub fn play_file(path: &Path, playback: &Playback) -> anyhow::Result<()> {
+    let Some(guard) = acquire(playback)? else {
+        log_dropped(playback);
+        return Ok(());
+    };
+    run_player(path, &guard, playback)?;
     Ok(())
 }
 
-pub fn play_bytes(bytes: &[u8], volume: f32) -> anyhow::Result<()> {
+pub fn play_bytes(bytes: &[u8], playback: &Playback) -> anyhow::Result<()> {
     let mut temp = tempfile::NamedTempFile::new().context("create temp file")?;
     std::io::Write::write_all(&mut temp, bytes).context("write audio bytes")?;
-    play_file(temp.path(), volume)
+    play_file(temp.path(), playback)
+}
+
+/// Plays audio while it is still being synthesized. The first chunk starts
+/// as soon as it arrives (and it has the speaker); chunks that arrive while
+/// one segment plays are joined into the next.
+pub fn play_stream(chunks: Receiver<Pcm>, playback: &Playback) -> anyhow::Result<()> {
+    let Ok(first) = chunks.recv() else {
+        return Ok(());
+    };
+    let Some(guard) = acquire(playback)? else {
+        log_dropped(playback);
+        return Ok(());
+    };
+
+    let mut next = Some(first);
+    while let Some(mut segment) = next.take() {
+        for chunk in chunks.try_iter() {
+            segment.append(chunk)?;
+        }
+        let mut temp = tempfile::NamedTempFile::new().context("create temp file")?;
+        std::io::Write::write_all(&mut temp, &wav::encode(&segment)?)
+            .context("write audio bytes")?;
+        if !run_player(temp.path(), &guard, playback)? {
+            return Ok(());
+        }
+        next = chunks.recv().ok();
+    }
+    Ok(())
+}
+
+fn log_dropped(playback: &Playback) {
+    tracing::info!(
+        priority = playback.priority.as_str(),
+        "playback queue busy; dropping clip"
+    );
+}
+
+/// Plays `path` while holding the speaker. Returns false if a
+/// higher-priority clip preempted it.
+fn run_player(path: &Path, guard: &PlaybackGuard, playback: &Playback) -> anyhow::Result<bool> {
+    let status = if guard.preempted() {
+        None
+    } else {
+        let player = Command::new("afplay")
+            .arg("-v")
+            .arg(playback.volume.to_string())
+            .arg(path)
+            .spawn()
+            .with_context(|| format!("play audio with afplay: {}", path.display()))?;
+        guard.wait(player)?
+    };
+
+    let Some(status) = status else {
+        tracing::info!(
+            priority = playback.priority.as_str(),
+            "clip preempted by higher-priority playback"
+        );
+        return Ok(false);
+    };
+    if !status.success() {
+        bail!("afplay exited with status {status}");
+    }
+
+    Ok(true)
 }
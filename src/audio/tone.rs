@@ -0,0 +1,109 @@
+use crate::config::{Envelope, Note, ToneSpec, ValidationReport, Waveform};
+use crate::events::EventType;
+use std::f32::consts::TAU;
+
+use super::wav::Pcm;
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Longest note or rest a spec may ask for.
+pub const MAX_NOTE_MS: u64 = 5_000;
+
+/// The tones used when an event has neither a configured spec nor a WAV
+/// file, matching the bundled files in spirit.
+pub fn default_spec(event_type: EventType) -> ToneSpec {
+    let (notes, waveform): (&[(f32, u64)], Waveform) = match event_type {
+        // Ascending fifth, C5 to G5.
+        EventType::AgentYield => (&[(523.25, 110), (783.99, 180)], Waveform::Sine),
+        // Rising C major triad.
+        EventType::DecisionRequired => (
+            &[(523.25, 100), (659.25, 100), (783.99, 180)],
+            Waveform::Triangle,
+        ),
+        // Descending G4, E4, C4.
+        EventType::ErrorRetry => (
+            &[(392.0, 120), (329.63, 120), (261.63, 220)],
+            Waveform::Square,
+        ),
+    };
+    ToneSpec {
+        notes: notes.iter().map(|&(hz, ms)| Note { hz, ms }).collect(),
+        waveform,
+        envelope: Envelope::default(),
+        volume: match waveform {
+            // Square waves sound much louder at the same peak.
+            Waveform::Square => 0.3,
+            _ => 0.6,
+        },
+    }
+}
+
+/// Renders `spec` as mono PCM.
+pub fn render(spec: &ToneSpec) -> Pcm {
+    let mut samples = Vec::new();
+    let peak = spec.volume.clamp(0.0, 1.0) * f32::from(i16::MAX);
+    for note in &spec.notes {
+        let frames = frames_for(note.ms.min(MAX_NOTE_MS));
+        let attack = frames_for(spec.envelope.attack_ms).min(frames / 2).max(1);
+        let release = frames_for(spec.envelope.release_ms).min(frames / 2).max(1);
+        for frame in 0..frames {
+            if note.hz <= 0.0 {
+                samples.push(0);
+                continue;
+            }
+            let phase = (frame as f32 * note.hz / SAMPLE_RATE as f32).fract();
+            let gain = (frame as f32 / attack as f32)
+                .min((frames - frame) as f32 / release as f32)
+                .min(1.0);
+            samples.push((oscillate(spec.waveform, phase) * gain * peak) as i16);
+        }
+    }
+    Pcm {
+        sample_rate: SAMPLE_RATE,
+        channels: 1,
+        samples,
+    }
+}
+
+/// Reports problems in a configured spec under `path`.
+pub fn check(path: &str, spec: &ToneSpec, report: &mut ValidationReport) {
+    if spec.notes.is_empty() {
+        report.push(format!("{path}.notes"), "must list at least one note");
+    }
+    for (index, note) in spec.notes.iter().enumerate() {
+        if !(0.0..=20_000.0).contains(&note.hz) {
+            report.push(
+                format!("{path}.notes[{index}].hz"),
+                "must be between 0 and 20000",
+            );
+        }
+        if note.ms == 0 || note.ms > MAX_NOTE_MS {
+            report.push(
+                format!("{path}.notes[{index}].ms"),
+                format!("must be between 1 and {MAX_NOTE_MS}"),
+            );
+        }
+    }
+    if !(0.0..=1.0).contains(&spec.volume) {
+        report.push(format!("{path}.volume"), "must be between 0.0 and 1.0");
+    }
+}
+
+fn frames_for(ms: u64) -> usize {
+    (ms * u64::from(SAMPLE_RATE) / 1000) as usize
+}
+
+/// One cycle of `waveform` at `phase` in [0, 1), between -1 and 1.
+fn oscillate(waveform: Waveform, phase: f32) -> f32 {
+    match waveform {
+        Waveform::Sine => (phase * TAU).sin(),
+        Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        Waveform::Square => {
+            if phase < 0.5 {
+                1.0
+            } else {
+                -1.0
+            }
+        }
+        Waveform::Sawtooth => 2.0 * phase - 1.0,
+    }
+}
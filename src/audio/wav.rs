@@ -0,0 +1,127 @@
+use anyhow::{bail, Context};
+use std::io::Cursor;
+use std::time::Duration;
+
+pub enum Part {
+    Audio(Vec<u8>),
+    Silence(Duration),
+}
+
+#[derive(Debug, Clone)]
+pub struct Pcm {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+impl Pcm {
+    pub fn silence(sample_rate: u32, channels: u16, duration: Duration) -> Self {
+        let frames = (duration.as_secs_f64() * f64::from(sample_rate)) as usize;
+        Self {
+            sample_rate,
+            channels,
+            samples: vec![0; frames * usize::from(channels)],
+        }
+    }
+
+    /// Appends `other`, which must share this clip's format.
+    pub fn append(&mut self, other: Pcm) -> anyhow::Result<()> {
+        if other.sample_rate != self.sample_rate || other.channels != self.channels {
+            bail!(
+                "cannot stitch {}Hz/{}ch audio onto {}Hz/{}ch",
+                other.sample_rate,
+                other.channels,
+                self.sample_rate,
+                self.channels
+            );
+        }
+        self.samples.extend(other.samples);
+        Ok(())
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> anyhow::Result<Pcm> {
+    let mut reader = hound::WavReader::new(Cursor::new(bytes)).context("read wav header")?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .map(|s| s.map(|v| (v.clamp(-1.0, 1.0) * 32767.0) as i16))
+            .collect::<Result<Vec<_>, _>>()
+            .context("read float samples")?,
+        hound::SampleFormat::Int => {
+            let shift = i32::from(spec.bits_per_sample) - 16;
+            reader
+                .samples::<i32>()
+                .map(|s| {
+                    s.map(|v| {
+                        if shift >= 0 {
+                            (v >> shift) as i16
+                        } else {
+                            (v << -shift) as i16
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .context("read int samples")?
+        }
+    };
+
+    Ok(Pcm {
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        samples,
+    })
+}
+
+pub fn encode(pcm: &Pcm) -> anyhow::Result<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: pcm.channels,
+        sample_rate: pcm.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+    for sample in &pcm.samples {
+        writer.write_sample(*sample)?;
+    }
+    writer.finalize()?;
+    Ok(cursor.into_inner())
+}
+
+/// Concatenates WAV clips and silences into a single 16-bit WAV.
+///
+/// Every clip must share the sample rate and channel count of the first one.
+pub fn stitch(parts: &[Part]) -> anyhow::Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(parts.len());
+    for part in parts {
+        decoded.push(match part {
+            Part::Audio(bytes) => Some(decode(bytes)?),
+            Part::Silence(_) => None,
+        });
+    }
+
+    let Some(first) = decoded.iter().flatten().next() else {
+        bail!("no audio to stitch");
+    };
+    let mut out = Pcm {
+        sample_rate: first.sample_rate,
+        channels: first.channels,
+        samples: Vec::new(),
+    };
+
+    for (part, pcm) in parts.iter().zip(decoded) {
+        match pcm {
+            Some(pcm) => out.append(pcm)?,
+            None => {
+                if let Part::Silence(duration) = part {
+                    out.append(Pcm::silence(out.sample_rate, out.channels, *duration))?;
+                }
+            }
+        }
+    }
+
+    encode(&out)
+}
@@ -1,5 +1,6 @@
 use clap::{Args, Parser, Subcommand};
 
+use crate::config::{ConfigFormat, Layer};
 use crate::events::{EventType, Source};
 
 #[derive(Parser, Debug)]
@@ -14,6 +15,22 @@ pub struct Cli {
 
     #[arg(short, long, global = true, help = "Enable verbose logging")]
     pub verbose: bool,
+
+    #[arg(
+        long = "set",
+        global = true,
+        value_name = "KEY=VALUE",
+        help = "Override a config value (dotted key, repeatable)"
+    )]
+    pub overrides: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Config profile to apply (overrides AGENT_CHIME_PROFILE)"
+    )]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -23,8 +40,11 @@ pub enum Commands {
     Models(ModelsArgs),
     TestTts(TestTtsArgs),
     Config(ConfigArgs),
-    #[command(name = "__synthesize", hide = true)]
-    InternalSynthesize(InternalSynthesizeArgs),
+    Profile(ProfileArgs),
+    Daemon(DaemonArgs),
+    Assets(AssetsArgs),
+    #[command(name = "__worker", hide = true)]
+    InternalWorker(WorkerArgs),
 }
 
 #[derive(Args, Debug)]
@@ -77,21 +97,148 @@ pub struct TestTtsArgs {
 
 #[derive(Args, Debug)]
 pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: Option<ConfigCommand>,
+
     #[arg(long, help = "Show current config as JSON")]
     pub show: bool,
 
     #[arg(long, help = "Create default config file")]
     pub init: bool,
 
+    #[arg(long, value_enum, default_value_t, help = "File format for --init")]
+    pub format: ConfigFormat,
+
+    #[arg(
+        long,
+        help = "With --init, write ./agent-chime.<ext> instead of the user config"
+    )]
+    pub project: bool,
+
     #[arg(long, help = "Validate configuration")]
     pub validate: bool,
+
+    #[arg(long, help = "Show each effective value and the layer it came from")]
+    pub explain: bool,
+
+    #[arg(long, help = "Output --validate results as JSON")]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print a JSON Schema for the config file
+    Schema,
+    /// Print the effective value of a dotted key
+    Get {
+        #[arg(value_name = "KEY", help = "Dotted key, e.g. tts.pocket_tts.voice")]
+        key: String,
+    },
+    /// Set a dotted key in the user or project config
+    Set {
+        #[arg(value_name = "KEY", help = "Dotted key, e.g. tts.pocket_tts.voice")]
+        key: String,
+
+        #[arg(
+            value_name = "VALUE",
+            allow_hyphen_values = true,
+            help = "JSON literal (number, bool, array, ...) or plain string"
+        )]
+        value: String,
+
+        #[command(flatten)]
+        target: LayerTarget,
+    },
+    /// Remove a dotted key from the user or project config
+    Unset {
+        #[arg(value_name = "KEY", help = "Dotted key, e.g. tts.pocket_tts.voice")]
+        key: String,
+
+        #[command(flatten)]
+        target: LayerTarget,
+    },
+    /// Rewrite a config file in the current schema, keeping a backup
+    Migrate {
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "File to migrate instead of the user/project config"
+        )]
+        file: Option<std::path::PathBuf>,
+
+        #[command(flatten)]
+        target: LayerTarget,
+    },
 }
 
 #[derive(Args, Debug)]
-pub struct InternalSynthesizeArgs {
-    #[arg(long, help = "Text to synthesize")]
-    pub text: String,
+pub struct LayerTarget {
+    #[arg(long, conflicts_with = "user", help = "Write the project config file")]
+    pub project: bool,
 
-    #[arg(long, help = "TTS backend")]
-    pub backend: Option<String>,
+    #[arg(long, help = "Write the user config file (default)")]
+    pub user: bool,
+}
+
+impl LayerTarget {
+    pub fn layer(&self) -> Layer {
+        if self.project {
+            Layer::Project
+        } else {
+            Layer::User
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// List profiles, marking the active one
+    List,
+    /// Make a profile active until changed
+    Use {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Forget the saved profile
+    Clear,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkerArgs {
+    #[arg(long, value_name = "DIR", help = "Runtime dir to listen in")]
+    pub runtime_dir: std::path::PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    #[arg(long, help = "Load the TTS model on first use instead of at startup")]
+    pub no_preload: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AssetsArgs {
+    #[command(subcommand)]
+    pub command: AssetsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AssetsCommand {
+    /// Extract the bundled earcons and voicepack into the data dir
+    Install {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Install here instead of the data dir"
+        )]
+        dir: Option<std::path::PathBuf>,
+
+        #[arg(long, help = "Overwrite files that already exist")]
+        force: bool,
+    },
 }
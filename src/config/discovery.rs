@@ -0,0 +1,122 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_FILE_NAMES: &[&str] = &[
+    "agent-chime.json",
+    "agent-chime.toml",
+    "agent-chime.yaml",
+    "agent-chime.yml",
+    ".agent-chime.json",
+    ".agent-chime.toml",
+    ".agent-chime.yaml",
+    ".agent-chime.yml",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct DiscoveryConfig {
+    #[serde(default)]
+    pub stop_at: StopAt,
+}
+
+/// How far up from the cwd to search for a project config.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StopAt {
+    /// Stop at the enclosing git root (or the filesystem root outside a repo).
+    #[default]
+    Git,
+    /// Stop at the home directory (or the filesystem root outside it).
+    Home,
+    /// Search all the way to the filesystem root.
+    Root,
+    /// Only check the cwd.
+    Cwd,
+}
+
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    pub found: Option<PathBuf>,
+    pub boundary: PathBuf,
+    pub stop_at: StopAt,
+    /// The boundary actually used: `Root` when `stop_at` names a git root or
+    /// home directory that doesn't enclose the cwd.
+    pub scope: StopAt,
+}
+
+pub fn find_project_config(cwd: &Path, config: &DiscoveryConfig) -> Discovery {
+    let (boundary, scope) = boundary_for(cwd, config.stop_at);
+    let mut found = None;
+
+    for dir in cwd.ancestors() {
+        if let Some(path) = PROJECT_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            found = Some(path);
+            break;
+        }
+        if dir == boundary {
+            break;
+        }
+    }
+
+    Discovery {
+        found,
+        boundary,
+        stop_at: config.stop_at,
+        scope,
+    }
+}
+
+fn boundary_for(cwd: &Path, stop_at: StopAt) -> (PathBuf, StopAt) {
+    let root = (
+        cwd.ancestors().last().unwrap_or(cwd).to_path_buf(),
+        StopAt::Root,
+    );
+    match stop_at {
+        StopAt::Cwd => (cwd.to_path_buf(), StopAt::Cwd),
+        StopAt::Root => root,
+        StopAt::Git => cwd
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(|dir| (dir.to_path_buf(), StopAt::Git))
+            .unwrap_or(root),
+        StopAt::Home => directories::BaseDirs::new()
+            .map(|base| base.home_dir().to_path_buf())
+            .filter(|home| cwd.starts_with(home))
+            .map(|home| (home, StopAt::Home))
+            .unwrap_or(root),
+    }
+}
+
+impl Discovery {
+    pub fn describe(&self) -> String {
+        let mut scope = match self.scope {
+            StopAt::Git => "git root",
+            StopAt::Home => "home directory",
+            StopAt::Root => "filesystem root",
+            StopAt::Cwd => "cwd",
+        }
+        .to_string();
+        match self.stop_at {
+            StopAt::Git if self.scope != StopAt::Git => scope.push_str(" (not in a git repo)"),
+            StopAt::Home if self.scope != StopAt::Home => scope.push_str(" (outside home)"),
+            _ => {}
+        }
+        match &self.found {
+            Some(path) => format!(
+                "project config found at {} (searched up to {} {})",
+                path.display(),
+                scope,
+                self.boundary.display()
+            ),
+            None => format!(
+                "no project config between cwd and {} {}",
+                scope,
+                self.boundary.display()
+            ),
+        }
+    }
+}
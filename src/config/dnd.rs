@@ -0,0 +1,313 @@
+use super::{Config, Mode, ValidationReport};
+use crate::events::{Event, Priority};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDateTime, Utc, Weekday};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Quiet hours: weekly windows during which notifications are muted or
+/// downgraded, unless their priority is allowed to break through.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DndConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// IANA name (`Europe/Berlin`), `UTC`, a fixed offset (`+02:00`) or
+    /// `local` (the default).
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub windows: Vec<TimeWindow>,
+    /// What each priority does during quiet hours; unlisted priorities are
+    /// muted.
+    #[serde(default = "default_breakthrough")]
+    pub breakthrough: HashMap<Priority, DndPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimeWindow {
+    /// Days the window starts on; empty means every day.
+    #[serde(default)]
+    pub days: Vec<Day>,
+    /// `HH:MM` local time.
+    pub start: String,
+    /// `HH:MM` (or `24:00`); at or before `start` means the next day.
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Day {
+    #[serde(alias = "monday")]
+    Mon,
+    #[serde(alias = "tuesday")]
+    Tue,
+    #[serde(alias = "wednesday")]
+    Wed,
+    #[serde(alias = "thursday")]
+    Thu,
+    #[serde(alias = "friday")]
+    Fri,
+    #[serde(alias = "saturday")]
+    Sat,
+    #[serde(alias = "sunday")]
+    Sun,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DndPolicy {
+    #[serde(default)]
+    pub action: DndAction,
+    /// Multiplier applied to the volume when the notification still plays.
+    #[serde(default = "default_policy_volume")]
+    pub volume: f32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DndAction {
+    #[default]
+    Mute,
+    /// Play the earcon instead of speaking.
+    Earcon,
+    Play,
+}
+
+/// Quiet-hours status at a point in time, as shown by `system-info`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DndState {
+    pub active: bool,
+    pub timezone: String,
+    pub local_time: String,
+    /// Local end of the current quiet window, when active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            timezone: None,
+            windows: Vec::new(),
+            breakthrough: default_breakthrough(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_policy_volume() -> f32 {
+    1.0
+}
+
+fn default_breakthrough() -> HashMap<Priority, DndPolicy> {
+    HashMap::from([(
+        Priority::High,
+        DndPolicy {
+            action: DndAction::Play,
+            volume: 0.5,
+        },
+    )])
+}
+
+impl Day {
+    fn matches(self, weekday: Weekday) -> bool {
+        let day = match weekday {
+            Weekday::Mon => Day::Mon,
+            Weekday::Tue => Day::Tue,
+            Weekday::Wed => Day::Wed,
+            Weekday::Thu => Day::Thu,
+            Weekday::Fri => Day::Fri,
+            Weekday::Sat => Day::Sat,
+            Weekday::Sun => Day::Sun,
+        };
+        day == self
+    }
+}
+
+enum Zone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl Zone {
+    fn parse(spec: Option<&str>) -> Result<Self, String> {
+        let spec = spec.map(str::trim).unwrap_or("");
+        if spec.is_empty() || spec.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        if spec.eq_ignore_ascii_case("utc") || spec == "Z" {
+            return Ok(Zone::Fixed(FixedOffset::east_opt(0).expect("zero offset")));
+        }
+        if let Some(sign) = spec.chars().next().filter(|c| *c == '+' || *c == '-') {
+            let digits: String = spec[1..].chars().filter(|c| *c != ':').collect();
+            let (hours, minutes) = match digits.len() {
+                2 => (digits.parse::<i32>().ok(), Some(0)),
+                4 => (digits[..2].parse().ok(), digits[2..].parse().ok()),
+                _ => (None, None),
+            };
+            let seconds = match (hours, minutes) {
+                (Some(h), Some(m)) if m < 60 => (h * 60 + m) * 60,
+                _ => return Err(format!("invalid UTC offset {spec}; use +HH:MM")),
+            };
+            let seconds = if sign == '-' { -seconds } else { seconds };
+            return FixedOffset::east_opt(seconds)
+                .map(Zone::Fixed)
+                .ok_or_else(|| format!("UTC offset out of range: {spec}"));
+        }
+        spec.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| format!("unknown timezone {spec}"))
+    }
+
+    fn local(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => now.with_timezone(&Local).naive_local(),
+            Zone::Fixed(offset) => now.with_timezone(offset).naive_local(),
+            Zone::Named(tz) => now.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+/// Minutes since midnight for `HH:MM`, allowing `24:00`.
+fn parse_clock(value: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid time {value:?}; use HH:MM");
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 || hours > 24 || (hours == 24 && minutes != 0) {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+impl TimeWindow {
+    /// Whether `now` falls inside this window in `timezone` (see
+    /// `DndConfig::timezone`).
+    pub fn contains(&self, now: DateTime<Utc>, timezone: Option<&str>) -> Result<bool, String> {
+        let local = Zone::parse(timezone)?.local(now);
+        Ok(self.active_until(local).is_some())
+    }
+
+    /// End of this window if `local` falls inside it.
+    fn active_until(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = parse_clock(&self.start).ok()?;
+        let end = parse_clock(&self.end).ok()?;
+        let length = if end > start {
+            end - start
+        } else {
+            end + 24 * 60 - start
+        };
+
+        // A window that wraps past midnight may have started yesterday.
+        [0, 1].into_iter().find_map(|days_back| {
+            let date = local.date() - Duration::days(days_back);
+            if !self.days.is_empty() && !self.days.iter().any(|d| d.matches(date.weekday())) {
+                return None;
+            }
+            let opens = date.and_hms_opt(0, 0, 0)? + Duration::minutes(start.into());
+            let closes = opens + Duration::minutes(length.into());
+            (opens <= local && local < closes).then_some(closes)
+        })
+    }
+}
+
+impl DndConfig {
+    pub fn state_at(&self, now: DateTime<Utc>) -> Result<DndState, String> {
+        let zone = Zone::parse(self.timezone.as_deref())?;
+        let local = zone.local(now);
+        let until = if self.enabled {
+            self.windows
+                .iter()
+                .filter_map(|window| window.active_until(local))
+                .max()
+        } else {
+            None
+        };
+
+        Ok(DndState {
+            active: until.is_some(),
+            timezone: self.timezone.clone().unwrap_or_else(|| "local".to_string()),
+            local_time: local.format("%a %H:%M").to_string(),
+            until: until.map(|end| end.format("%a %H:%M").to_string()),
+        })
+    }
+
+    pub fn policy_for(&self, priority: Priority) -> DndPolicy {
+        self.breakthrough
+            .get(&priority)
+            .cloned()
+            .unwrap_or(DndPolicy {
+                action: DndAction::Mute,
+                volume: default_policy_volume(),
+            })
+    }
+}
+
+impl Config {
+    /// Applies quiet hours to a resolved config at `now`. Returns `None` when
+    /// the notification should be muted.
+    pub fn for_quiet_hours(&self, event: &Event, now: DateTime<Utc>) -> Option<Config> {
+        let state = match self.dnd.state_at(now) {
+            Ok(state) => state,
+            Err(message) => {
+                tracing::warn!(%message, "ignoring quiet hours");
+                return Some(self.clone());
+            }
+        };
+        if !state.active {
+            return Some(self.clone());
+        }
+
+        let policy = self.dnd.policy_for(event.priority);
+        tracing::debug!(?policy, until = ?state.until, "quiet hours active");
+        let mut config = self.clone();
+        match policy.action {
+            DndAction::Mute => return None,
+            DndAction::Earcon => {
+                config.voicepack.enabled = false;
+                if let Some(event_config) = config.events.get_mut(&event.event_type) {
+                    if event_config.mode == Mode::Tts {
+                        event_config.mode = Mode::Earcon;
+                    }
+                }
+            }
+            DndAction::Play => {}
+        }
+        config.volume = (config.volume * policy.volume).clamp(0.0, 1.0);
+        Some(config)
+    }
+}
+
+pub(crate) fn check(dnd: &DndConfig, report: &mut ValidationReport) {
+    check_timezone(dnd.timezone.as_deref(), "dnd.timezone", report);
+    for (index, window) in dnd.windows.iter().enumerate() {
+        check_window(window, &format!("dnd.windows[{index}]"), report);
+    }
+    for (priority, policy) in &dnd.breakthrough {
+        if !(0.0..=1.0).contains(&policy.volume) {
+            report.push(
+                format!("dnd.breakthrough.{}.volume", priority.as_str()),
+                "must be between 0.0 and 1.0",
+            );
+        }
+    }
+}
+
+pub(crate) fn check_timezone(timezone: Option<&str>, path: &str, report: &mut ValidationReport) {
+    if let Err(message) = Zone::parse(timezone) {
+        report.push(path, message);
+    }
+}
+
+pub(crate) fn check_window(window: &TimeWindow, prefix: &str, report: &mut ValidationReport) {
+    for (field, value) in [("start", &window.start), ("end", &window.end)] {
+        if let Err(message) = parse_clock(value) {
+            report.push(format!("{prefix}.{field}"), message);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use super::layers::{self, Layer, LoadOptions};
+use super::{discovery, Config, ConfigFormat, ValidationReport};
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A layer document held in memory in place of its file, so an edit can be
+/// checked against the full layer stack before anything is written.
+pub(crate) struct Staged {
+    pub layer: Layer,
+    pub path: PathBuf,
+    pub value: Value,
+}
+
+impl Config {
+    /// Effective value at a dotted key, after all layers are merged.
+    pub fn get_key(options: &LoadOptions, key: &str) -> anyhow::Result<Value> {
+        let config = Self::load_with(options)?;
+        // Round-trip through text so f32 fields read back as written.
+        let value: Value = serde_json::from_str(&serde_json::to_string(&config)?)?;
+        lookup(&value, &segments(key)?)
+            .cloned()
+            .with_context(|| format!("unknown config key: {key}"))
+    }
+
+    /// Sets a dotted key in the user or project config file and returns the
+    /// file's path. `raw` is used as a JSON literal when that type-checks at
+    /// `key`, otherwise as a string.
+    pub fn set_key(
+        options: &LoadOptions,
+        layer: Layer,
+        key: &str,
+        raw: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let parsed = layers::parse_value(raw);
+        let mut candidates = vec![Some(parsed.clone())];
+        if !parsed.is_string() {
+            candidates.push(Some(Value::String(raw.to_string())));
+        }
+        Self::edit_layer(options, layer, key, candidates)
+    }
+
+    /// Removes a dotted key from the user or project config file so lower
+    /// layers apply again.
+    pub fn unset_key(options: &LoadOptions, layer: Layer, key: &str) -> anyhow::Result<PathBuf> {
+        Self::edit_layer(options, layer, key, vec![None])
+    }
+
+    /// File that `set_key`/`unset_key` write for `layer`. The project layer
+    /// uses the discovered project file, else `./agent-chime.json`.
+    pub fn layer_path(options: &LoadOptions, layer: Layer) -> anyhow::Result<PathBuf> {
+        match layer {
+            Layer::User => Self::default_path(options),
+            Layer::Project => {
+                let cwd = options.cwd()?;
+                let settings = Self::load_with(options)
+                    .map(|config| config.discovery)
+                    .unwrap_or_default();
+                Ok(discovery::find_project_config(&cwd, &settings)
+                    .found
+                    .unwrap_or_else(|| cwd.join("agent-chime.json")))
+            }
+            other => bail!("cannot write the {other} layer; use the user or project config"),
+        }
+    }
+
+    /// Applies the first candidate edit that introduces no new validation
+    /// problems, then writes the file atomically. Unknown keys are kept
+    /// because the raw document is edited, not a parsed `Config`.
+    fn edit_layer(
+        options: &LoadOptions,
+        layer: Layer,
+        key: &str,
+        candidates: Vec<Option<Value>>,
+    ) -> anyhow::Result<PathBuf> {
+        let segments = segments(key)?;
+        let path = Self::layer_path(options, layer)?;
+        let original = if path.is_file() {
+            let format = ConfigFormat::from_path(&path);
+            let raw =
+                fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+            if !format.edits_in_place(&raw) {
+                bail!(
+                    "{} has comments or layout that rewriting it as {} would lose; \
+                     edit it by hand",
+                    path.display(),
+                    format.extension().to_uppercase()
+                );
+            }
+            // Editing would also upgrade the file, so that has to be asked for.
+            let original = layers::parse_document(&path)?;
+            let from = super::migrate::upgrade(&mut original.clone())
+                .with_context(|| format!("migrate config at {}", path.display()))?;
+            if from != super::SCHEMA_VERSION {
+                bail!(
+                    "{} is at schema v{from}; run `agent-chime config migrate --file {}` \
+                     to upgrade it (keeping a backup) before editing",
+                    path.display(),
+                    path.display()
+                );
+            }
+            original
+        } else {
+            Value::Object(Map::new())
+        };
+        let defaults = serde_json::to_value(Self::default()).context("serialize defaults")?;
+
+        let issues_with = |value: Value| -> Vec<String> {
+            let staged = Staged {
+                layer,
+                path: path.clone(),
+                value,
+            };
+            Self::check_inner(options, Some(&staged))
+                .issues
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        let baseline = issues_with(original.clone());
+
+        let mut rejected: Option<Vec<String>> = None;
+        for candidate in candidates {
+            let mut edited = original.clone();
+            match candidate {
+                Some(value) => insert(&mut edited, Some(&defaults), &segments, value)?,
+                None => {
+                    if !remove(&mut edited, &segments) {
+                        bail!("{key} is not set in {}", path.display());
+                    }
+                }
+            }
+
+            let introduced: Vec<String> = issues_with(edited.clone())
+                .into_iter()
+                .filter(|issue| !baseline.contains(issue))
+                .collect();
+            if introduced.is_empty() {
+                write_atomic(&path, &edited)?;
+                return Ok(path);
+            }
+            rejected.get_or_insert(introduced);
+        }
+
+        let mut report = ValidationReport::default();
+        for issue in rejected.unwrap_or_default() {
+            report.push("", issue);
+        }
+        bail!("refusing to write {}:\n{report}", path.display())
+    }
+}
+
+fn segments(key: &str) -> anyhow::Result<Vec<&str>> {
+    let segments: Vec<&str> = key.split('.').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        bail!("empty config key");
+    }
+    Ok(segments)
+}
+
+fn find_key(map: &Map<String, Value>, segment: &str) -> Option<String> {
+    map.keys()
+        .find(|k| k.eq_ignore_ascii_case(segment))
+        .cloned()
+}
+
+fn index(items: &[Value], segment: &str) -> Option<usize> {
+    segment.parse().ok().filter(|i| *i < items.len())
+}
+
+fn lookup<'a>(value: &'a Value, segments: &[&str]) -> Option<&'a Value> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return Some(value);
+    };
+    let child = match value {
+        Value::Object(map) => map.get(&find_key(map, segment)?)?,
+        Value::Array(items) => &items[index(items, segment)?],
+        _ => return None,
+    };
+    lookup(child, rest)
+}
+
+/// Sets the value at `segments`, creating tables as needed. New keys take
+/// the spelling used by the defaults, so `events.agent_yield` lands on
+/// `events.AGENT_YIELD`.
+fn insert(
+    target: &mut Value,
+    defaults: Option<&Value>,
+    segments: &[&str],
+    value: Value,
+) -> anyhow::Result<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        *target = value;
+        return Ok(());
+    };
+
+    if let Value::Array(items) = target {
+        let i = index(items, segment)
+            .with_context(|| format!("no list element {segment} (have {})", items.len()))?;
+        return insert(&mut items[i], defaults.and_then(|d| d.get(i)), rest, value);
+    }
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(map) = target else {
+        unreachable!("target was just made an object");
+    };
+    let name = find_key(map, segment)
+        .or_else(|| {
+            defaults
+                .and_then(Value::as_object)
+                .and_then(|d| find_key(d, segment))
+        })
+        .unwrap_or_else(|| segment.to_string());
+    let defaults = defaults.and_then(|d| d.get(&name));
+    insert(
+        map.entry(name).or_insert(Value::Null),
+        defaults,
+        rest,
+        value,
+    )
+}
+
+/// Removes the value at `segments` and any tables left empty by it.
+fn remove(target: &mut Value, segments: &[&str]) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        return false;
+    };
+
+    match target {
+        Value::Object(map) => {
+            let Some(name) = find_key(map, segment) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return map.remove(&name).is_some();
+            }
+            let Some(child) = map.get_mut(&name) else {
+                return false;
+            };
+            let removed = remove(child, rest);
+            if removed && child.as_object().is_some_and(Map::is_empty) {
+                map.remove(&name);
+            }
+            removed
+        }
+        Value::Array(items) => {
+            let Some(i) = index(items, segment) else {
+                return false;
+            };
+            if rest.is_empty() {
+                items.remove(i);
+                true
+            } else {
+                remove(&mut items[i], rest)
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Writes `value` to `path` by way of a temp file. An existing file is
+/// edited rather than replaced where the format allows (see
+/// `ConfigFormat::render_edit`).
+pub(super) fn write_atomic(path: &Path, value: &Value) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let format = ConfigFormat::from_path(path);
+    let rendered = match fs::read_to_string(path) {
+        Ok(original) => format.render_edit(&original, value)?,
+        Err(_) => format.render(value)?,
+    };
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("invalid config path {}", path.display()))?
+        .to_string_lossy();
+    let tmp = path.with_file_name(format!(".{file_name}.tmp"));
+    fs::write(&tmp, rendered).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(())
+}
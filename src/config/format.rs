@@ -0,0 +1,245 @@
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike};
+
+/// On-disk config syntax, chosen by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Toml,
+    #[value(alias = "yml")]
+    Yaml,
+}
+
+pub const CONFIG_EXTENSIONS: &[&str] = &["json", "toml", "yaml", "yml"];
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn parse(self, raw: &str) -> anyhow::Result<Value> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(raw).context("invalid JSON"),
+            ConfigFormat::Toml => toml::from_str(raw).context("invalid TOML"),
+            ConfigFormat::Yaml => {
+                // An empty YAML document is `null`; treat it like `{}`.
+                let value: Value = serde_norway::from_str(raw).context("invalid YAML")?;
+                Ok(if value.is_null() {
+                    Value::Object(Default::default())
+                } else {
+                    value
+                })
+            }
+        }
+    }
+
+    pub fn render<T: Serialize>(self, value: &T) -> anyhow::Result<String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).context("render JSON"),
+            ConfigFormat::Toml => {
+                // TOML has no null; drop unset keys before rendering. Going
+                // through JSON text keeps f32 values short (0.8, not 0.800000011).
+                let json = serde_json::to_string(value).context("serialize config")?;
+                let mut value: Value = serde_json::from_str(&json)?;
+                strip_nulls(&mut value);
+                toml::to_string_pretty(&value).context("render TOML")
+            }
+            ConfigFormat::Yaml => serde_norway::to_string(value).context("render YAML"),
+        }
+    }
+
+    /// Renders `value` as an edit of `original`, the file's current text.
+    /// TOML keeps its comments, layout and key order, rewriting only the
+    /// keys that changed; the other formats are rendered afresh.
+    pub fn render_edit(self, original: &str, value: &Value) -> anyhow::Result<String> {
+        let ConfigFormat::Toml = self else {
+            return self.render(value);
+        };
+        let mut doc: DocumentMut = original.parse().context("invalid TOML")?;
+        let Value::Object(map) = value else {
+            bail!("config must be a table");
+        };
+        sync_table(doc.as_table_mut(), map, false);
+        Ok(doc.to_string())
+    }
+
+    /// Whether rewriting `original` would keep it as written, apart from
+    /// the edited keys.
+    pub fn edits_in_place(self, original: &str) -> bool {
+        match self {
+            ConfigFormat::Json | ConfigFormat::Toml => true,
+            // No comment-preserving YAML editor: only files that already
+            // look rendered can be rewritten without losing anything.
+            ConfigFormat::Yaml => self
+                .parse(original)
+                .and_then(|value| self.render(&value))
+                .is_ok_and(|rendered| rendered.trim_end() == original.trim_end()),
+        }
+    }
+}
+
+/// Makes `table` hold `map`, leaving entries whose value is unchanged
+/// untouched and keeping the decor (comments, spacing) of changed values.
+fn sync_table(table: &mut dyn TableLike, map: &Map<String, Value>, inline: bool) {
+    let stale: Vec<String> = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| map.get(key).is_none_or(Value::is_null))
+        .collect();
+    for key in stale {
+        table.remove(&key);
+    }
+    for (key, value) in map {
+        if value.is_null() {
+            continue;
+        }
+        match table.get_mut(key) {
+            Some(item) => sync_item(item, value, inline),
+            None => {
+                table.insert(key, new_item(value, inline));
+            }
+        }
+    }
+}
+
+fn sync_item(item: &mut Item, value: &Value, inline: bool) {
+    if let Value::Object(map) = value {
+        let inline = inline || item.is_inline_table();
+        if let Some(table) = item.as_table_like_mut() {
+            sync_table(table, map, inline);
+            return;
+        }
+    }
+    match (&mut *item, value) {
+        (Item::ArrayOfTables(tables), Value::Array(values))
+            if values.iter().all(Value::is_object) =>
+        {
+            while tables.len() > values.len() {
+                tables.remove(tables.len() - 1);
+            }
+            for (i, value) in values.iter().enumerate() {
+                let Value::Object(map) = value else {
+                    continue;
+                };
+                match tables.get_mut(i) {
+                    Some(table) => sync_table(table, map, false),
+                    None => {
+                        let mut table = Table::new();
+                        sync_table(&mut table, map, false);
+                        tables.push(table);
+                    }
+                }
+            }
+        }
+        (Item::Value(existing), _) if from_toml(existing) == *value => {}
+        (Item::Value(existing), _) => {
+            let decor = existing.decor().clone();
+            let mut replacement = to_toml(value);
+            *replacement.decor_mut() = decor;
+            *existing = replacement;
+        }
+        _ => *item = new_item(value, inline),
+    }
+}
+
+/// A fresh item for `value`: standard tables where TOML allows them, inline
+/// ones inside inline tables.
+fn new_item(value: &Value, inline: bool) -> Item {
+    match value {
+        Value::Object(map) if !inline => {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            sync_table(&mut table, map, false);
+            Item::Table(table)
+        }
+        Value::Array(values)
+            if !inline && !values.is_empty() && values.iter().all(Value::is_object) =>
+        {
+            let mut tables = ArrayOfTables::new();
+            for value in values {
+                if let Value::Object(map) = value {
+                    let mut table = Table::new();
+                    sync_table(&mut table, map, false);
+                    tables.push(table);
+                }
+            }
+            Item::ArrayOfTables(tables)
+        }
+        _ => Item::Value(to_toml(value)),
+    }
+}
+
+fn to_toml(value: &Value) -> toml_edit::Value {
+    match value {
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(values) => values
+            .iter()
+            .filter(|v| !v.is_null())
+            .map(to_toml)
+            .collect::<toml_edit::Array>()
+            .into(),
+        Value::Object(map) => map
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| (k.as_str(), to_toml(v)))
+            .collect::<InlineTable>()
+            .into(),
+        // Callers skip nulls; TOML has no way to write one.
+        Value::Null => "".into(),
+    }
+}
+
+fn from_toml(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(items) => Value::Array(items.iter().map(from_toml).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(k, v)| (k.to_string(), from_toml(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
@@ -0,0 +1,340 @@
+use super::format::ConfigFormat;
+use anyhow::{bail, Context};
+use directories::BaseDirs;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ENV_PREFIX: &str = "AGENT_CHIME_";
+pub const SYSTEM_CONFIG_DIR: &str = "/etc/agent-chime";
+
+/// Config layers, lowest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    Default,
+    System,
+    User,
+    Project,
+    Profile,
+    Env,
+    Cli,
+}
+
+impl Layer {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Layer::Default => "default",
+            Layer::System => "system",
+            Layer::User => "user",
+            Layer::Project => "project",
+            Layer::Profile => "profile",
+            Layer::Env => "env",
+            Layer::Cli => "cli",
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Extra inputs to `Config::load_with` that don't come from files.
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// `KEY=VALUE` pairs with dotted keys, e.g. `tts.pocket_tts.voice=azelma`.
+    pub overrides: Vec<String>,
+    /// Profile chosen with `--profile`; see `active_profile`.
+    pub profile: Option<String>,
+    /// Stand-ins for the process's surroundings, so a load can be resolved
+    /// against a scratch dir: the system and user config dirs, the dir
+    /// holding the saved profile, the cwd and the environment. `None` uses
+    /// the real one.
+    pub system_dir: Option<PathBuf>,
+    pub user_dir: Option<PathBuf>,
+    pub state_dir: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub env: Option<Vec<(String, String)>>,
+}
+
+impl LoadOptions {
+    pub fn system_dir(&self) -> PathBuf {
+        self.system_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(SYSTEM_CONFIG_DIR))
+    }
+
+    pub fn user_dir(&self) -> anyhow::Result<PathBuf> {
+        if let Some(dir) = &self.user_dir {
+            return Ok(dir.clone());
+        }
+        let base = BaseDirs::new().context("unable to resolve home directory")?;
+        Ok(base.config_dir().join("agent-chime"))
+    }
+
+    pub fn state_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.state_dir {
+            return Some(dir.clone());
+        }
+        let base = BaseDirs::new()?;
+        let dir = base.state_dir().unwrap_or_else(|| base.data_local_dir());
+        Some(dir.join("agent-chime"))
+    }
+
+    pub fn cwd(&self) -> anyhow::Result<PathBuf> {
+        match &self.cwd {
+            Some(cwd) => Ok(cwd.clone()),
+            None => std::env::current_dir().context("resolve current directory"),
+        }
+    }
+
+    /// Environment variables, sorted by name.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars = match &self.env {
+            Some(env) => env.clone(),
+            None => std::env::vars().collect(),
+        };
+        vars.sort();
+        vars
+    }
+
+    pub fn var(&self, name: &str) -> Option<String> {
+        match &self.env {
+            Some(env) => env
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone()),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
+
+/// What `Config::resolve` loaded and where each value came from.
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    pub files: Vec<(Layer, PathBuf)>,
+    pub discovery: Option<String>,
+    pub profile: Option<super::ActiveProfile>,
+    pub provenance: BTreeMap<String, Layer>,
+    /// What the env and CLI layers set, to lay back over the config once
+    /// per-source, per-project and rule overrides have been applied for an
+    /// event, so those two layers win there too. See `Config::pin`.
+    pub pinned: Value,
+}
+
+impl Resolution {
+    /// Layer that set `key` (a dotted path), or `Default` if none did.
+    pub fn layer_for(&self, key: &str) -> Layer {
+        layer_at(&self.provenance, key)
+    }
+
+    pub fn path_for(&self, layer: Layer) -> Option<&Path> {
+        self.files
+            .iter()
+            .find(|(l, _)| *l == layer)
+            .map(|(_, path)| path.as_path())
+    }
+}
+
+pub(crate) fn layer_at(provenance: &BTreeMap<String, Layer>, key: &str) -> Layer {
+    let mut current = key;
+    loop {
+        if let Some(layer) = provenance.get(current) {
+            return *layer;
+        }
+        match current.rfind(['.', '[']) {
+            Some(idx) => current = &current[..idx],
+            None => return Layer::Default,
+        }
+    }
+}
+
+/// Deep-merges JSON documents while recording which layer set each leaf.
+pub(crate) struct Merger {
+    pub value: Value,
+    pub provenance: BTreeMap<String, Layer>,
+}
+
+impl Merger {
+    pub fn new(base: Value) -> Self {
+        Self {
+            value: base,
+            provenance: BTreeMap::new(),
+        }
+    }
+
+    pub fn merge(&mut self, overlay: Value, layer: Layer) {
+        merge_value(&mut self.value, overlay, "", layer, &mut self.provenance);
+    }
+
+    /// Sets a single dotted key. Keys match existing ones case-insensitively
+    /// so `AGENT_CHIME_EVENTS__AGENT_YIELD__MODE` finds `events.AGENT_YIELD`.
+    pub fn set(&mut self, key: &str, value: Value, layer: Layer) -> anyhow::Result<()> {
+        let segments: Vec<&str> = key.split('.').filter(|s| !s.is_empty()).collect();
+        if segments.is_empty() {
+            bail!("empty config key");
+        }
+
+        let mut overlay = value;
+        let mut target = &self.value;
+        let mut resolved = Vec::with_capacity(segments.len());
+        for segment in &segments {
+            let name = target
+                .as_object()
+                .and_then(|map| map.keys().find(|k| k.eq_ignore_ascii_case(segment)))
+                .cloned()
+                .unwrap_or_else(|| segment.to_string());
+            target = target.get(&name).unwrap_or(&Value::Null);
+            resolved.push(name);
+        }
+        for name in resolved.into_iter().rev() {
+            let mut map = Map::new();
+            map.insert(name, overlay);
+            overlay = Value::Object(map);
+        }
+
+        self.merge(overlay, layer);
+        Ok(())
+    }
+
+    /// The values set by `layers`, as a document holding just those leaves.
+    pub fn extract(&self, layers: &[Layer]) -> Value {
+        let mut extracted = Value::Object(Map::new());
+        for (path, layer) in &self.provenance {
+            if !layers.contains(layer) {
+                continue;
+            }
+            let segments: Vec<&str> = path.split('.').collect();
+            let Some(value) = segments
+                .iter()
+                .try_fold(&self.value, |value, segment| value.get(*segment))
+            else {
+                continue;
+            };
+            let mut target = &mut extracted;
+            for segment in &segments {
+                if !target.is_object() {
+                    *target = Value::Object(Map::new());
+                }
+                let Value::Object(map) = target else {
+                    unreachable!("target was just made an object");
+                };
+                target = map.entry(segment.to_string()).or_insert(Value::Null);
+            }
+            *target = value.clone();
+        }
+        extracted
+    }
+
+    pub fn has_top_level(&self, key: &str) -> bool {
+        self.value
+            .as_object()
+            .is_some_and(|map| map.keys().any(|k| k.eq_ignore_ascii_case(key)))
+    }
+}
+
+fn merge_value(
+    target: &mut Value,
+    overlay: Value,
+    path: &str,
+    layer: Layer,
+    provenance: &mut BTreeMap<String, Layer>,
+) {
+    match (target, overlay) {
+        (Value::Object(target), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                let child = join_path(path, &key);
+                let slot = target.entry(key).or_insert(Value::Null);
+                merge_value(slot, value, &child, layer, provenance);
+            }
+        }
+        (target, overlay) => {
+            let prefix = format!("{path}.");
+            provenance.retain(|key, _| !key.starts_with(&prefix));
+            record_leaves(&overlay, path, layer, provenance);
+            *target = overlay;
+        }
+    }
+}
+
+fn record_leaves(
+    value: &Value,
+    path: &str,
+    layer: Layer,
+    provenance: &mut BTreeMap<String, Layer>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                record_leaves(child, &join_path(path, key), layer, provenance);
+            }
+        }
+        _ => {
+            provenance.insert(path.to_string(), layer);
+        }
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Parses a CLI or env value: JSON literals (numbers, bools, null, arrays,
+/// objects) are used as-is, anything else is taken as a string.
+pub fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+pub fn split_override(raw: &str) -> anyhow::Result<(&str, Value)> {
+    let (key, value) = raw
+        .split_once('=')
+        .with_context(|| format!("override must be KEY=VALUE: {raw}"))?;
+    Ok((key.trim(), parse_value(value)))
+}
+
+/// Maps `AGENT_CHIME_TTS__POCKET_TTS__VOICE` to `tts.pocket_tts.voice`.
+pub fn env_key(name: &str) -> Option<String> {
+    let rest = name.strip_prefix(ENV_PREFIX)?;
+    if rest.is_empty() {
+        return None;
+    }
+    Some(
+        rest.split("__")
+            .collect::<Vec<_>>()
+            .join(".")
+            .to_lowercase(),
+    )
+}
+
+/// Reads a config file and upgrades it to the current schema in memory.
+pub fn read_document(path: &Path) -> anyhow::Result<Value> {
+    let mut value = parse_document(path)?;
+    let from = super::migrate::upgrade(&mut value)
+        .with_context(|| format!("migrate config at {}", path.display()))?;
+    if from != super::SCHEMA_VERSION {
+        tracing::debug!(path = %path.display(), from, "upgraded config schema in memory");
+    }
+    Ok(value)
+}
+
+/// Reads a config file as written, without schema migrations.
+pub fn parse_document(path: &Path) -> anyhow::Result<Value> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read config at {}", path.display()))?;
+    let value = ConfigFormat::from_path(path)
+        .parse(&raw)
+        .with_context(|| format!("parse config at {}", path.display()))?;
+    if !value.is_object() {
+        bail!("config at {} must be a table/object", path.display());
+    }
+    Ok(value)
+}
@@ -0,0 +1,200 @@
+use super::{edit, layers, Config};
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Schema version written by this build. Renaming or restructuring a field
+/// means bumping this and appending a step to `MIGRATIONS`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [from_v0];
+
+/// Outcome of `Config::migrate_file`.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub path: PathBuf,
+    pub from: u32,
+    pub backup: Option<PathBuf>,
+}
+
+/// Upgrades a config document in place and returns the version it started
+/// at. Documents without `schema_version` are treated as version 0.
+pub fn upgrade(value: &mut Value) -> anyhow::Result<u32> {
+    let Some(map) = value.as_object_mut() else {
+        return Ok(SCHEMA_VERSION);
+    };
+
+    let from = match map.get("schema_version") {
+        None => 0,
+        Some(raw) => raw
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("schema_version must be a whole number, got {raw}"))?,
+    };
+    if from > SCHEMA_VERSION {
+        bail!(
+            "schema_version {from} is newer than this agent-chime supports ({SCHEMA_VERSION}); \
+             upgrade agent-chime"
+        );
+    }
+
+    for step in &MIGRATIONS[from as usize..] {
+        step(map);
+    }
+    map.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    Ok(from)
+}
+
+impl Config {
+    /// Rewrites `path` in the current schema, keeping the original next to
+    /// it as `<name>.v<old>.bak`. Returns `None` if it is already current.
+    pub fn migrate_file(path: &Path) -> anyhow::Result<Option<Migration>> {
+        let mut value = layers::parse_document(path)?;
+        let from = upgrade(&mut value)?;
+        if from == SCHEMA_VERSION {
+            return Ok(None);
+        }
+
+        serde_json::from_value::<Config>(value.clone())
+            .with_context(|| format!("{} does not parse after migration", path.display()))?;
+
+        let backup = backup_path(path, from);
+        fs::copy(path, &backup)
+            .with_context(|| format!("back up {} to {}", path.display(), backup.display()))?;
+        edit::write_atomic(path, &value)?;
+        Ok(Some(Migration {
+            path: path.to_path_buf(),
+            from,
+            backup: Some(backup),
+        }))
+    }
+}
+
+fn backup_path(path: &Path, from: u32) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut candidate = path.with_file_name(format!("{name}.v{from}.bak"));
+    let mut n = 1;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{name}.v{from}.{n}.bak"));
+        n += 1;
+    }
+    candidate
+}
+
+/// Version 0 covers unversioned configs: both earlier agent-chime-rs files
+/// (already in the current layout) and the Python agent-chime layout, which
+/// kept TTS settings at the top level, grouped cache limits under `cache`,
+/// named backend tables `pocket`/`qwen3` and spelled event keys in lowercase.
+fn from_v0(doc: &mut Map<String, Value>) {
+    move_key(doc, &["backend"], &["tts", "backend"]);
+    move_key(doc, &["voice"], &["tts", "voice"]);
+    move_key(doc, &["instruct"], &["tts", "instruct"]);
+    move_key(doc, &["timeout"], &["tts", "timeout_seconds"]);
+    move_key(doc, &["tts", "timeout"], &["tts", "timeout_seconds"]);
+    move_key(doc, &["tts", "pocket"], &["tts", "pocket_tts"]);
+    move_key(doc, &["tts", "qwen3"], &["tts", "qwen3_tts"]);
+    move_key(doc, &["cache", "dir"], &["cache_dir"]);
+    move_key(doc, &["cache", "max_mb"], &["cache_max_mb"]);
+    move_key(doc, &["cache", "max_entries"], &["cache_max_entries"]);
+    if doc
+        .get("cache")
+        .and_then(Value::as_object)
+        .is_some_and(Map::is_empty)
+    {
+        doc.remove("cache");
+    }
+    move_key(doc, &["earcons"], &["earcons_dir"]);
+
+    if let Some(Value::Object(tts)) = doc.get_mut("tts") {
+        // Python accepted fractional timeouts.
+        if let Some(secs) = tts.get("timeout_seconds").and_then(Value::as_f64) {
+            if secs.fract() != 0.0 && secs > 0.0 {
+                tts.insert("timeout_seconds".to_string(), (secs.ceil() as u64).into());
+            }
+        }
+    }
+
+    if let Some(Value::String(manifest)) = doc.get("voicepack").cloned() {
+        let mut voicepack = Map::new();
+        voicepack.insert("enabled".to_string(), true.into());
+        voicepack.insert("manifest_path".to_string(), manifest.into());
+        doc.insert("voicepack".to_string(), Value::Object(voicepack));
+    }
+
+    normalize_events(doc.get_mut("events"));
+    if let Some(Value::Object(sources)) = doc.get_mut("sources") {
+        for source in sources.values_mut() {
+            normalize_events(source.get_mut("events"));
+        }
+    }
+    if let Some(Value::Array(projects)) = doc.get_mut("projects") {
+        for project in projects {
+            normalize_events(project.get_mut("events"));
+        }
+    }
+}
+
+/// Moves the value at `from` to `to`, creating tables on the way. If `to` is
+/// already set the newer key wins and the old one is dropped.
+fn move_key(doc: &mut Map<String, Value>, from: &[&str], to: &[&str]) {
+    let Some((last, parents)) = from.split_last() else {
+        return;
+    };
+    let mut source = &mut *doc;
+    for key in parents {
+        match source.get_mut(*key) {
+            Some(Value::Object(child)) => source = child,
+            _ => return,
+        }
+    }
+    let Some(value) = source.remove(*last) else {
+        return;
+    };
+
+    let Some((last, parents)) = to.split_last() else {
+        return;
+    };
+    let mut target = &mut *doc;
+    for key in parents {
+        let slot = target
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !slot.is_object() {
+            *slot = Value::Object(Map::new());
+        }
+        let Value::Object(child) = slot else {
+            unreachable!("slot was just made an object");
+        };
+        target = child;
+    }
+    if target.contains_key(*last) {
+        tracing::warn!(
+            from = %from.join("."),
+            to = %to.join("."),
+            "both legacy and current config keys are set; keeping the current one"
+        );
+        return;
+    }
+    target.insert(last.to_string(), value);
+}
+
+/// `agent_yield` / `agent-yield` keys become `AGENT_YIELD`, and modes are
+/// lowercased.
+fn normalize_events(events: Option<&mut Value>) {
+    let Some(Value::Object(events)) = events else {
+        return;
+    };
+    let entries = std::mem::take(events);
+    for (key, mut value) in entries {
+        if let Some(Value::String(mode)) = value.get_mut("mode") {
+            *mode = mode.to_lowercase();
+        }
+        let key = key.replace('-', "_").to_uppercase();
+        events.entry(key).or_insert(value);
+    }
+}
@@ -0,0 +1,912 @@
+use crate::events::{EventType, Source};
+use anyhow::Context;
+use directories::BaseDirs;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod discovery;
+mod dnd;
+mod edit;
+mod format;
+mod layers;
+mod migrate;
+mod profile;
+mod project;
+mod rules;
+mod validate;
+
+pub use discovery::{find_project_config, Discovery, DiscoveryConfig, StopAt, PROJECT_FILE_NAMES};
+pub use dnd::{Day, DndAction, DndConfig, DndPolicy, DndState, TimeWindow};
+pub use format::{ConfigFormat, CONFIG_EXTENSIONS};
+pub use layers::{Layer, LoadOptions, Resolution, ENV_PREFIX, SYSTEM_CONFIG_DIR};
+pub use migrate::{Migration, SCHEMA_VERSION};
+pub use profile::{active_profile, save_active_profile, ActiveProfile, ProfileSource, PROFILE_ENV};
+pub use project::{ProjectConfig, VoicePackOverride};
+pub(crate) use rules::compile_regex;
+pub use rules::{Rule, RuleActions, RuleConditions};
+pub use validate::{Issue, ValidationReport};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Config {
+    /// Layout version of the file; older documents are upgraded on load.
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub tts: TtsConfig,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub events: HashMap<EventType, EventConfig>,
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub cache_max_mb: Option<u64>,
+    #[serde(default)]
+    pub cache_max_entries: Option<usize>,
+    /// Where concurrent `agent-chime` processes share the playback queue,
+    /// debounce state and daemon socket.
+    #[serde(default)]
+    pub runtime_dir: Option<PathBuf>,
+    /// Where `assets install` puts the default earcons and voicepack.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub earcons_dir: Option<PathBuf>,
+    /// Synthesized earcons, used instead of the WAV files for these events.
+    #[serde(default)]
+    pub earcon_tones: HashMap<EventType, ToneSpec>,
+    #[serde(default)]
+    pub voicepack: VoicePackConfig,
+    #[serde(default)]
+    pub sources: HashMap<Source, SourceConfig>,
+    #[serde(default)]
+    pub projects: Vec<ProjectConfig>,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub dnd: DndConfig,
+    #[serde(default)]
+    pub debounce: DebounceConfig,
+    #[serde(default)]
+    pub playback: PlaybackConfig,
+    /// Ordered notification policy, applied before the broker.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// Named partial configs; the active one is merged over the file layers.
+    #[serde(default)]
+    pub profiles: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub backend: Option<String>,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub instruct: Option<String>,
+    /// Deadline for producing speech, across `backend` and every fallback;
+    /// 0 disables it.
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Backends tried in order when `backend` fails, before the earcon.
+    #[serde(default)]
+    pub fallback_backends: Vec<String>,
+    /// Per-backend time limits in seconds, within `timeout_seconds`. A backend
+    /// without one gets an even share of what is left for the chain.
+    #[serde(default)]
+    pub backend_timeouts: HashMap<String, u64>,
+    #[serde(default = "default_allow_downloads")]
+    pub allow_downloads: bool,
+    #[serde(default)]
+    pub pocket_tts: PocketTtsConfig,
+    #[serde(default)]
+    pub qwen3_tts: Qwen3TtsConfig,
+    #[serde(default)]
+    pub command: CommandTtsConfig,
+    #[serde(default)]
+    pub http: HttpTtsConfig,
+    #[serde(default)]
+    pub speech_dispatcher: SpeechDispatcherConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct PocketTtsConfig {
+    pub variant: Option<String>,
+    pub voice: Option<String>,
+    pub use_metal: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct Qwen3TtsConfig {
+    pub model: Option<String>,
+    pub tokenizer: Option<String>,
+    pub speaker: Option<String>,
+    pub language: Option<String>,
+    pub ref_audio: Option<String>,
+    pub ref_text: Option<String>,
+    pub device: Option<String>,
+}
+
+/// An external program for the `command` backend, run without a shell.
+/// `{text}` in an argument is replaced with the text, which otherwise goes to
+/// stdin; `{out}` is replaced with a WAV path for the program to write, which
+/// otherwise is read from stdout.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct CommandTtsConfig {
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// An OpenAI-compatible `/v1/audio/speech` endpoint for the `http` backend.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct HttpTtsConfig {
+    /// Full endpoint URL, e.g. `http://127.0.0.1:8880/v1/audio/speech`.
+    pub url: Option<String>,
+    pub model: Option<String>,
+    /// Used when `tts.voice` is unset.
+    pub voice: Option<String>,
+    /// `wav` (default) or `pcm`, raw 16-bit mono at `pcm_sample_rate`.
+    pub response_format: Option<String>,
+    pub pcm_sample_rate: Option<u32>,
+    /// Extra request headers, e.g. `Authorization`.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// The local speech-dispatcher daemon, for the `speech-dispatcher` backend.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct SpeechDispatcherConfig {
+    /// SSIP socket; defaults to `$SPEECHD_ADDRESS`, then
+    /// `$XDG_RUNTIME_DIR/speech-dispatcher/speechd.sock`.
+    pub socket: Option<PathBuf>,
+    /// Synthesis voice name, used when `tts.voice` is unset.
+    pub voice: Option<String>,
+    /// -100 to 100, 0 being the daemon's default.
+    pub rate: Option<i32>,
+    /// -100 to 100, 0 being the daemon's default; scaled by `volume`.
+    pub volume: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Per-source overrides; unset fields fall through to the global config.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct SourceConfig {
+    #[serde(default)]
+    pub volume: Option<f32>,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub backend: Option<String>,
+    #[serde(default)]
+    pub events: HashMap<EventType, EventOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct EventOverride {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub mode: Option<Mode>,
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Drops bursts of repeated events, shared across `notify` processes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DebounceConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Repeats of an event type from the same session within this window
+    /// are dropped.
+    #[serde(default = "default_debounce_ms")]
+    pub window_ms: u64,
+    /// Per-event-type replacements for `window_ms`.
+    #[serde(default)]
+    pub events: HashMap<EventType, u64>,
+    /// Event types that coalesce across sessions: once one plays, the same
+    /// type from any session is dropped for `coalesce_ms`.
+    #[serde(default = "default_coalesce")]
+    pub coalesce: Vec<EventType>,
+    #[serde(default = "default_coalesce_ms")]
+    pub coalesce_ms: u64,
+}
+
+/// How clips from concurrent `notify` processes share the speaker.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlaybackConfig {
+    /// How long a clip waits for the speaker, whatever its priority. When it
+    /// runs out the clip is dropped; 0 drops a clip that can't play right
+    /// away.
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u64,
+    /// A higher-priority clip stops the one playing instead of waiting.
+    #[serde(default = "default_enabled")]
+    pub preempt: bool,
+}
+
+/// An earcon built from tones rather than read from a WAV file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ToneSpec {
+    /// Played in order.
+    pub notes: Vec<Note>,
+    #[serde(default)]
+    pub waveform: Waveform,
+    #[serde(default)]
+    pub envelope: Envelope,
+    /// Peak level from 0.0 to 1.0, before the global `volume`.
+    #[serde(default = "default_tone_volume")]
+    pub volume: f32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Note {
+    /// Pitch in Hz; 0 is a rest.
+    pub hz: f32,
+    pub ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Triangle,
+    Square,
+    Sawtooth,
+}
+
+/// Linear fades at the start and end of every note, so notes don't click.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Envelope {
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: u64,
+    #[serde(default = "default_release_ms")]
+    pub release_ms: u64,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack_ms: default_attack_ms(),
+            release_ms: default_release_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct VoicePackConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub manifest_path: Option<PathBuf>,
+    #[serde(default)]
+    pub routes: Vec<VoicePackRoute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VoicePackRoute {
+    pub pattern: String,
+    pub phrases: Vec<String>,
+    #[serde(default)]
+    pub events: Vec<EventType>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Tts,
+    Earcon,
+    Silent,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        Self::load_with(&LoadOptions::default())
+    }
+
+    pub fn load_with(options: &LoadOptions) -> anyhow::Result<Self> {
+        Ok(Self::resolve(options)?.0)
+    }
+
+    /// Loads the effective config and reports where each value came from.
+    ///
+    /// Layers are deep-merged in order: defaults < system < user < project <
+    /// `AGENT_CHIME_*` environment variables < CLI overrides.
+    pub fn resolve(options: &LoadOptions) -> anyhow::Result<(Self, Resolution)> {
+        Self::resolve_inner(options, None, None)
+    }
+
+    /// Loads every layer leniently and reports all problems: syntax errors,
+    /// type errors (by path) and failed semantic checks.
+    pub fn check(options: &LoadOptions) -> ValidationReport {
+        Self::check_inner(options, None)
+    }
+
+    fn check_inner(options: &LoadOptions, staged: Option<&edit::Staged>) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        match Self::resolve_inner(options, Some(&mut report), staged) {
+            Ok((config, resolution)) => {
+                let mut semantic = config.validation_report();
+                for issue in &mut semantic.issues {
+                    let layer = resolution.layer_for(&issue.path);
+                    issue.origin = Some(match resolution.path_for(layer) {
+                        Some(path) => format!("{layer}: {}", path.display()),
+                        None => layer.to_string(),
+                    });
+                }
+                report.extend(semantic);
+            }
+            Err(err) => report.push("", format!("{err:#}")),
+        }
+        report
+    }
+
+    fn resolve_inner(
+        options: &LoadOptions,
+        mut report: Option<&mut ValidationReport>,
+        staged: Option<&edit::Staged>,
+    ) -> anyhow::Result<(Self, Resolution)> {
+        let defaults = serde_json::to_value(Self::default()).context("serialize defaults")?;
+        let mut merger = layers::Merger::new(defaults);
+        let mut resolution = Resolution::default();
+
+        let mut files = Vec::new();
+        if let Some(system) = find_config_file(&options.system_dir()) {
+            files.push((Layer::System, system));
+        }
+        let user = match staged.filter(|s| s.layer == Layer::User) {
+            Some(staged) => Some(staged.path.clone()),
+            None => Self::default_path(options)
+                .ok()
+                .filter(|path| path.is_file()),
+        };
+        if let Some(user) = user {
+            files.push((Layer::User, user));
+        }
+
+        for (layer, path) in files {
+            if let Some(value) = Self::read_layer(&path, layer, staged, report.as_deref_mut())? {
+                merger.merge(value, layer);
+            }
+            resolution.files.push((layer, path));
+        }
+
+        if let Ok(cwd) = options.cwd() {
+            let settings: DiscoveryConfig = merger
+                .value
+                .get("discovery")
+                .cloned()
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default();
+            let discovery = discovery::find_project_config(&cwd, &settings);
+            resolution.discovery = Some(discovery.describe());
+            let project = match staged.filter(|s| s.layer == Layer::Project) {
+                Some(staged) => Some(staged.path.clone()),
+                None => discovery.found,
+            };
+            if let Some(project) = project {
+                let layer = Layer::Project;
+                if let Some(value) =
+                    Self::read_layer(&project, layer, staged, report.as_deref_mut())?
+                {
+                    merger.merge(value, layer);
+                }
+                resolution.files.push((layer, project));
+            }
+        }
+
+        if let Some(active) = profile::active_profile(options) {
+            let overlay = merger
+                .value
+                .get("profiles")
+                .and_then(|profiles| profiles.get(&active.name))
+                .cloned();
+            match overlay {
+                Some(serde_json::Value::Object(mut overlay)) => {
+                    overlay.remove("profiles");
+                    merger.merge(serde_json::Value::Object(overlay), Layer::Profile);
+                    resolution.profile = Some(active);
+                }
+                Some(_) => {
+                    let message = format!("profile {} must be a table/object", active.name);
+                    match report.as_deref_mut() {
+                        Some(report) => report.push(format!("profiles.{}", active.name), message),
+                        None => anyhow::bail!(message),
+                    }
+                }
+                None if active.source == ProfileSource::State => {
+                    // A stale `profile use` must not break every notification.
+                    tracing::warn!(profile = %active.name, "saved profile no longer exists; ignoring");
+                }
+                None => {
+                    let message =
+                        format!("unknown profile {} (from {})", active.name, active.source);
+                    match report.as_deref_mut() {
+                        Some(report) => {
+                            report.push_from(active.source.as_str(), "profile", message)
+                        }
+                        None => anyhow::bail!(message),
+                    }
+                }
+            }
+        }
+
+        let env = options
+            .vars()
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX));
+        for (name, raw) in env {
+            let Some(key) = layers::env_key(&name) else {
+                continue;
+            };
+            let top = key.split('.').next().unwrap_or_default();
+            if !merger.has_top_level(top) {
+                continue;
+            }
+            merger
+                .set(&key, layers::parse_value(&raw), Layer::Env)
+                .with_context(|| format!("apply {name}"))?;
+        }
+
+        for raw in &options.overrides {
+            let (key, value) = layers::split_override(raw)?;
+            merger
+                .set(key, value, Layer::Cli)
+                .with_context(|| format!("apply override {raw}"))?;
+        }
+
+        resolution.pinned = merger.extract(&[Layer::Env, Layer::Cli]);
+        let merged = match report {
+            Some(report) => {
+                let provenance = merger.provenance.clone();
+                validate::collect_type_errors::<Config>(merger.value, |path, message| {
+                    let layer = layers::layer_at(&provenance, &path);
+                    report.push_from(layer.as_str(), path, message);
+                })
+            }
+            None => merger.value,
+        };
+        let mut config: Config = serde_json::from_value(merged).context("parse merged config")?;
+        config.apply_defaults();
+        resolution.provenance = merger.provenance;
+        Ok((config, resolution))
+    }
+
+    /// Reads one layer file. In lenient mode problems go to `report` and the
+    /// layer is cleaned up (or skipped) instead of failing the load. A staged
+    /// document for this layer is used instead of the file on disk.
+    fn read_layer(
+        path: &Path,
+        layer: Layer,
+        staged: Option<&edit::Staged>,
+        report: Option<&mut ValidationReport>,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let origin = format!("{layer}: {}", path.display());
+        let read = || match staged.filter(|s| s.layer == layer) {
+            Some(staged) => Ok(staged.value.clone()),
+            None => layers::read_document(path),
+        };
+        let Some(report) = report else {
+            let value = read()?;
+            serde_json::from_value::<Config>(value.clone())
+                .with_context(|| format!("parse config at {}", path.display()))?;
+            return Ok(Some(value));
+        };
+
+        let value = match read() {
+            Ok(value) => value,
+            Err(err) => {
+                report.push_from(origin, "", format!("{err:#}"));
+                return Ok(None);
+            }
+        };
+        Ok(Some(validate::collect_type_errors::<Config>(
+            value,
+            |path, message| report.push_from(origin.clone(), path, message),
+        )))
+    }
+
+    /// Loads a single JSON, TOML or YAML file (by extension) over the defaults.
+    pub fn load_from_path(path: &Path) -> anyhow::Result<Self> {
+        let value = layers::read_document(path)?;
+        let mut config: Config = serde_json::from_value(value)
+            .with_context(|| format!("parse config at {}", path.display()))?;
+        config.apply_defaults();
+        Ok(config)
+    }
+
+    /// Writes the default config as the user config, or as
+    /// `./agent-chime.<ext>` when `project` is set.
+    pub fn init_default(
+        options: &LoadOptions,
+        format: ConfigFormat,
+        project: bool,
+    ) -> anyhow::Result<PathBuf> {
+        let path = if project {
+            PathBuf::from(format!("agent-chime.{}", format.extension()))
+        } else {
+            options
+                .user_dir()?
+                .join(format!("config.{}", format.extension()))
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let config = Self::default();
+        fs::write(&path, format.render(&config)?)?;
+        Ok(path)
+    }
+
+    /// Path of the user config: the first existing `config.{json,toml,yaml,yml}`
+    /// in the config dir, else `config.json`.
+    pub fn default_path(options: &LoadOptions) -> anyhow::Result<PathBuf> {
+        let dir = options.user_dir()?;
+        Ok(find_config_file(&dir).unwrap_or_else(|| dir.join("config.json")))
+    }
+
+    pub fn default_cache_dir(&self) -> anyhow::Result<PathBuf> {
+        if let Some(dir) = &self.cache_dir {
+            return Ok(dir.clone());
+        }
+        let base = BaseDirs::new().context("unable to resolve home directory")?;
+        Ok(base.cache_dir().join("agent-chime"))
+    }
+
+    pub fn cache_limits(&self) -> (u64, usize) {
+        let max_mb = self.cache_max_mb.unwrap_or(100);
+        let max_entries = self.cache_max_entries.unwrap_or(1000);
+        (max_mb * 1024 * 1024, max_entries)
+    }
+
+    pub fn default_runtime_dir(&self) -> PathBuf {
+        self.runtime_dir
+            .clone()
+            .unwrap_or_else(crate::runtime::default_dir)
+    }
+
+    pub fn default_data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.clone().or_else(crate::assets::default_dir)
+    }
+
+    /// `path` under the data dir, if `assets install` has put it there.
+    fn installed_asset(&self, path: &str) -> Option<PathBuf> {
+        self.default_data_dir()
+            .map(|dir| dir.join(path))
+            .filter(|path| path.exists())
+    }
+
+    pub fn default_earcons_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.earcons_dir {
+            return Some(dir.clone());
+        }
+
+        let local = PathBuf::from("earcons");
+        if local.exists() {
+            return Some(local);
+        }
+
+        self.installed_asset("earcons")
+    }
+
+    pub fn voicepack_manifest_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.voicepack.manifest_path {
+            return Some(path.clone());
+        }
+
+        let local = PathBuf::from("voicepack").join("manifest.json");
+        if local.exists() {
+            return Some(local);
+        }
+
+        self.installed_asset("voicepack/manifest.json")
+    }
+
+    /// Returns the effective config for `source` with its overrides applied.
+    pub fn for_source(&self, source: Source) -> Config {
+        let mut config = self.clone();
+        let Some(overrides) = self.sources.get(&source) else {
+            return config;
+        };
+
+        if let Some(volume) = overrides.volume {
+            config.volume = volume;
+        }
+        if let Some(voice) = &overrides.voice {
+            config.tts.voice = Some(voice.clone());
+        }
+        if let Some(backend) = &overrides.backend {
+            config.tts.backend = Some(backend.clone());
+        }
+
+        config.apply_event_overrides(&overrides.events);
+
+        config
+    }
+
+    /// Lays `pinned` (see `Resolution::pinned`) back over this config, so
+    /// env and CLI values beat the per-event overrides applied since.
+    pub fn pin(&self, pinned: &serde_json::Value) -> anyhow::Result<Config> {
+        if pinned.as_object().is_none_or(|map| map.is_empty()) {
+            return Ok(self.clone());
+        }
+        let mut merger =
+            layers::Merger::new(serde_json::to_value(self).context("serialize config")?);
+        merger.merge(pinned.clone(), Layer::Cli);
+        let mut config: Config =
+            serde_json::from_value(merger.value).context("apply env and CLI overrides")?;
+        config.apply_defaults();
+        Ok(config)
+    }
+
+    fn apply_event_overrides(&mut self, overrides: &HashMap<EventType, EventOverride>) {
+        for (event_type, event_override) in overrides {
+            let event_config = self
+                .events
+                .entry(*event_type)
+                .or_insert_with(|| EventConfig::default_for(*event_type));
+            event_override.apply(event_config);
+        }
+    }
+
+    fn apply_defaults(&mut self) {
+        for event_type in [
+            EventType::AgentYield,
+            EventType::DecisionRequired,
+            EventType::ErrorRetry,
+        ] {
+            match self.events.get_mut(&event_type) {
+                Some(event_config) => {
+                    if event_config.template.is_none() {
+                        event_config.template = Some(event_type.default_template().to_string());
+                    }
+                }
+                None => {
+                    self.events
+                        .insert(event_type, EventConfig::default_for(event_type));
+                }
+            }
+        }
+
+        if self.tts.pocket_tts.variant.is_none() {
+            self.tts.pocket_tts.variant = Some("b6369a24".to_string());
+        }
+
+        if self.tts.pocket_tts.voice.is_none() {
+            self.tts.pocket_tts.voice = Some("alba".to_string());
+        }
+
+        if self.tts.pocket_tts.use_metal.is_none() {
+            self.tts.pocket_tts.use_metal = Some(false);
+        }
+
+        if self.tts.qwen3_tts.speaker.is_none() {
+            self.tts.qwen3_tts.speaker = Some("Ryan".to_string());
+        }
+
+        if self.tts.qwen3_tts.language.is_none() {
+            self.tts.qwen3_tts.language = Some("English".to_string());
+        }
+
+        if self.tts.qwen3_tts.device.is_none() {
+            self.tts.qwen3_tts.device = Some("auto".to_string());
+        }
+
+        if self.cache_max_mb.is_none() {
+            self.cache_max_mb = Some(100);
+        }
+
+        if self.cache_max_entries.is_none() {
+            self.cache_max_entries = Some(1000);
+        }
+
+        if self.voicepack.routes.is_empty() {
+            self.voicepack.routes = Vec::new();
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut events = HashMap::new();
+        events.insert(
+            EventType::AgentYield,
+            EventConfig::default_for(EventType::AgentYield),
+        );
+        events.insert(
+            EventType::DecisionRequired,
+            EventConfig::default_for(EventType::DecisionRequired),
+        );
+        events.insert(
+            EventType::ErrorRetry,
+            EventConfig::default_for(EventType::ErrorRetry),
+        );
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tts: TtsConfig::default(),
+            volume: default_volume(),
+            events,
+            cache_dir: None,
+            cache_max_mb: Some(100),
+            cache_max_entries: Some(1000),
+            runtime_dir: None,
+            data_dir: None,
+            earcons_dir: None,
+            earcon_tones: HashMap::new(),
+            voicepack: VoicePackConfig::default(),
+            sources: HashMap::new(),
+            projects: Vec::new(),
+            discovery: DiscoveryConfig::default(),
+            dnd: DndConfig::default(),
+            debounce: DebounceConfig::default(),
+            playback: PlaybackConfig::default(),
+            rules: Vec::new(),
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+impl EventConfig {
+    pub fn default_for(event_type: EventType) -> Self {
+        let mode = match event_type {
+            EventType::ErrorRetry => Mode::Earcon,
+            _ => Mode::Tts,
+        };
+
+        Self {
+            enabled: true,
+            mode,
+            template: Some(event_type.default_template().to_string()),
+        }
+    }
+}
+
+impl EventOverride {
+    pub fn apply(&self, event_config: &mut EventConfig) {
+        if let Some(enabled) = self.enabled {
+            event_config.enabled = enabled;
+        }
+        if let Some(mode) = self.mode {
+            event_config.mode = mode;
+        }
+        if let Some(template) = &self.template {
+            event_config.template = Some(template.clone());
+        }
+    }
+}
+
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("config.{ext}")))
+        .find(|path| path.is_file())
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_allow_downloads() -> bool {
+    true
+}
+
+fn default_tone_volume() -> f32 {
+    0.6
+}
+
+fn default_attack_ms() -> u64 {
+    5
+}
+
+fn default_release_ms() -> u64 {
+    40
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_debounce_ms() -> u64 {
+    2000
+}
+
+fn default_coalesce() -> Vec<EventType> {
+    vec![EventType::AgentYield]
+}
+
+fn default_coalesce_ms() -> u64 {
+    3000
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            window_ms: default_debounce_ms(),
+            events: HashMap::new(),
+            coalesce: default_coalesce(),
+            coalesce_ms: default_coalesce_ms(),
+        }
+    }
+}
+
+fn default_max_wait_ms() -> u64 {
+    10_000
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            max_wait_ms: default_max_wait_ms(),
+            preempt: default_enabled(),
+        }
+    }
+}
+
+fn default_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+fn default_volume() -> f32 {
+    0.8
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            backend: Some("pocket-tts".to_string()),
+            voice: None,
+            instruct: None,
+            timeout_seconds: default_timeout_seconds(),
+            fallback_backends: Vec::new(),
+            backend_timeouts: HashMap::new(),
+            allow_downloads: default_allow_downloads(),
+            pocket_tts: PocketTtsConfig {
+                variant: Some("b6369a24".to_string()),
+                voice: Some("alba".to_string()),
+                use_metal: Some(false),
+            },
+            qwen3_tts: Qwen3TtsConfig {
+                model: None,
+                tokenizer: None,
+                speaker: Some("Ryan".to_string()),
+                language: Some("English".to_string()),
+                ref_audio: None,
+                ref_text: None,
+                device: Some("auto".to_string()),
+            },
+            command: CommandTtsConfig::default(),
+            http: HttpTtsConfig::default(),
+            speech_dispatcher: SpeechDispatcherConfig::default(),
+        }
+    }
+}
+
+impl TtsConfig {
+    /// The time limit for a single call to `backend`: its `backend_timeouts`
+    /// entry, else `timeout_seconds`. `None` when neither is set.
+    pub fn timeout_for(&self, backend: &str) -> Option<Duration> {
+        let seconds = match self.backend_timeouts.get(backend) {
+            Some(&seconds) if seconds > 0 => seconds,
+            _ => self.timeout_seconds,
+        };
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+}
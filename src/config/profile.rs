@@ -0,0 +1,91 @@
+use super::layers::LoadOptions;
+use super::Config;
+use anyhow::Context;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+pub const PROFILE_ENV: &str = "AGENT_CHIME_PROFILE";
+
+/// Where the active profile name came from, highest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileSource {
+    Cli,
+    Env,
+    State,
+}
+
+impl ProfileSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProfileSource::Cli => "--profile",
+            ProfileSource::Env => PROFILE_ENV,
+            ProfileSource::State => "profile use",
+        }
+    }
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActiveProfile {
+    pub name: String,
+    pub source: ProfileSource,
+}
+
+/// Picks the active profile: `--profile`, then `AGENT_CHIME_PROFILE`, then
+/// the name saved by `agent-chime profile use`. At most one small file is
+/// read, so this is cheap enough for every `notify`.
+pub fn active_profile(options: &LoadOptions) -> Option<ActiveProfile> {
+    let pick = |name: String, source| {
+        let name = name.trim().to_string();
+        (!name.is_empty()).then_some(ActiveProfile { name, source })
+    };
+
+    if let Some(name) = options.profile.clone() {
+        return pick(name, ProfileSource::Cli);
+    }
+    if let Some(name) = options.var(PROFILE_ENV) {
+        return pick(name, ProfileSource::Env);
+    }
+    let saved = fs::read_to_string(state_path(options)?).ok()?;
+    pick(saved, ProfileSource::State)
+}
+
+/// File holding the profile chosen with `profile use`.
+pub fn state_path(options: &LoadOptions) -> Option<PathBuf> {
+    Some(options.state_dir()?.join("profile"))
+}
+
+/// Persists `name` as the active profile, or clears it with `None`.
+pub fn save_active_profile(options: &LoadOptions, name: Option<&str>) -> anyhow::Result<()> {
+    let path = state_path(options).context("unable to resolve home directory")?;
+    let Some(name) = name else {
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, format!("{name}\n")).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(())
+}
+
+impl Config {
+    pub fn profile_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
@@ -0,0 +1,175 @@
+use super::{Config, EventOverride, VoicePackRoute};
+use crate::events::EventType;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Overrides applied when the working directory matches `path` or the git
+/// `origin` remote matches `remote`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub remote: Option<String>,
+    #[serde(default)]
+    pub volume: Option<f32>,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub voicepack: Option<VoicePackOverride>,
+    #[serde(default)]
+    pub events: HashMap<EventType, EventOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct VoicePackOverride {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub manifest_path: Option<PathBuf>,
+    #[serde(default)]
+    pub routes: Option<Vec<VoicePackRoute>>,
+}
+
+impl ProjectConfig {
+    fn matches(&self, cwd: &Path, remote: &mut RemoteLookup) -> bool {
+        if self.path.is_none() && self.remote.is_none() {
+            return false;
+        }
+
+        if let Some(pattern) = &self.path {
+            let expanded = expand_home(pattern);
+            let Some(regex) = glob_regex(&expanded, true) else {
+                return false;
+            };
+            if !cwd
+                .ancestors()
+                .any(|dir| regex.is_match(&dir.to_string_lossy()))
+            {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.remote {
+            let Some(regex) = glob_regex(pattern, false) else {
+                return false;
+            };
+            match remote.get(cwd) {
+                Some(url) if regex.is_match(url) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    fn apply(&self, config: &mut Config) {
+        if let Some(volume) = self.volume {
+            config.volume = volume;
+        }
+        if let Some(voice) = &self.voice {
+            config.tts.voice = Some(voice.clone());
+        }
+        if let Some(voicepack) = &self.voicepack {
+            if let Some(enabled) = voicepack.enabled {
+                config.voicepack.enabled = enabled;
+            }
+            if let Some(path) = &voicepack.manifest_path {
+                config.voicepack.manifest_path = Some(path.clone());
+            }
+            if let Some(routes) = &voicepack.routes {
+                config.voicepack.routes = routes.clone();
+            }
+        }
+        config.apply_event_overrides(&self.events);
+    }
+}
+
+impl Config {
+    /// Returns the effective config for `cwd`, applying every matching
+    /// `projects` entry in order.
+    pub fn for_project(&self, cwd: &Path) -> Config {
+        let mut config = self.clone();
+        let mut remote = RemoteLookup::default();
+        for project in &self.projects {
+            if project.matches(cwd, &mut remote) {
+                tracing::debug!(
+                    path = ?project.path,
+                    remote = ?project.remote,
+                    "project overrides matched"
+                );
+                project.apply(&mut config);
+            }
+        }
+        config
+    }
+}
+
+#[derive(Default)]
+struct RemoteLookup {
+    url: Option<Option<String>>,
+}
+
+impl RemoteLookup {
+    fn get(&mut self, cwd: &Path) -> Option<&str> {
+        self.url
+            .get_or_insert_with(|| git_remote_url(cwd))
+            .as_deref()
+    }
+}
+
+fn git_remote_url(cwd: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+pub(crate) fn expand_home(pattern: &str) -> String {
+    if let Some(rest) = pattern.strip_prefix("~/") {
+        if let Some(base) = directories::BaseDirs::new() {
+            return base.home_dir().join(rest).to_string_lossy().into_owned();
+        }
+    }
+    pattern.to_string()
+}
+
+/// Translates a shell-style glob into an anchored regex. With `path_aware`,
+/// `*` and `?` stop at `/` while `**` crosses directories.
+pub(crate) fn glob_regex(pattern: &str, path_aware: bool) -> Option<Regex> {
+    let pattern = pattern.trim_end_matches('/');
+    let mut out = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push_str(".*");
+            }
+            '*' if path_aware => out.push_str("[^/]*"),
+            '*' => out.push_str(".*"),
+            '?' if path_aware => out.push_str("[^/]"),
+            '?' => out.push('.'),
+            _ => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    match Regex::new(&out) {
+        Ok(regex) => Some(regex),
+        Err(err) => {
+            tracing::warn!(pattern, error = %err, "invalid project pattern");
+            None
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use super::dnd::{self, TimeWindow};
+use super::project::{expand_home, glob_regex};
+use super::{Config, EventConfig, Mode, ValidationReport};
+use crate::events::{Event, EventType, Priority, Source};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// One entry of `rules`: when every condition in `when` holds, the actions
+/// in `then` are applied.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct Rule {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub when: RuleConditions,
+    #[serde(default)]
+    pub then: RuleActions,
+}
+
+/// Every condition that is set must match; an empty `when` matches all events.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct RuleConditions {
+    #[serde(default)]
+    pub source: Vec<Source>,
+    #[serde(default)]
+    pub event: Vec<EventType>,
+    #[serde(default)]
+    pub priority: Vec<Priority>,
+    /// Regex searched for in the summary.
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Expected values in the hook payload, keyed by JSON pointer
+    /// (e.g. `/tool_name`).
+    #[serde(default)]
+    pub context: BTreeMap<String, Value>,
+    /// Glob matched against the working directory or any ancestor.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub time: Option<TimeWindow>,
+    /// Timezone for `time`; same forms as `dnd.timezone`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct RuleActions {
+    #[serde(default)]
+    pub mode: Option<Mode>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub instruct: Option<String>,
+    #[serde(default)]
+    pub volume: Option<f32>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Drop the notification entirely.
+    #[serde(default)]
+    pub suppress: bool,
+    /// Skip the remaining rules.
+    #[serde(default)]
+    pub stop: bool,
+}
+
+impl RuleConditions {
+    fn matches(&self, event: &Event, cwd: Option<&Path>, now: DateTime<Utc>) -> bool {
+        if !self.source.is_empty() && !self.source.contains(&event.source) {
+            return false;
+        }
+        if !self.event.is_empty() && !self.event.contains(&event.event_type) {
+            return false;
+        }
+        if !self.priority.is_empty() && !self.priority.contains(&event.priority) {
+            return false;
+        }
+
+        if let Some(pattern) = &self.summary {
+            let Ok(regex) = self.summary_regex(pattern) else {
+                return false;
+            };
+            if !regex.is_match(event.summary.as_deref().unwrap_or("")) {
+                return false;
+            }
+        }
+
+        for (pointer, expected) in &self.context {
+            let actual = event.context.as_ref().and_then(|ctx| ctx.pointer(pointer));
+            if actual != Some(expected) {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.path {
+            let Some(regex) = glob_regex(&expand_home(pattern), true) else {
+                return false;
+            };
+            let Some(cwd) = cwd else {
+                return false;
+            };
+            if !cwd
+                .ancestors()
+                .any(|dir| regex.is_match(&dir.to_string_lossy()))
+            {
+                return false;
+            }
+        }
+
+        if let Some(window) = &self.time {
+            match window.contains(now, self.timezone.as_deref()) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(message) => {
+                    tracing::warn!(%message, "rule time window not evaluated");
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn summary_regex(&self, pattern: &str) -> Result<Regex, String> {
+        compile_regex(pattern, self.case_sensitive)
+    }
+}
+
+impl RuleActions {
+    fn apply(&self, event: &mut Event, config: &mut Config) {
+        if let Some(priority) = self.priority {
+            event.priority = priority;
+        }
+        if self.mode.is_some() || self.template.is_some() {
+            let event_config = config
+                .events
+                .entry(event.event_type)
+                .or_insert_with(|| EventConfig::default_for(event.event_type));
+            if let Some(mode) = self.mode {
+                event_config.mode = mode;
+            }
+            if let Some(template) = &self.template {
+                event_config.template = Some(template.clone());
+            }
+        }
+        if let Some(voice) = &self.voice {
+            config.tts.voice = Some(voice.clone());
+        }
+        if let Some(instruct) = &self.instruct {
+            config.tts.instruct = Some(instruct.clone());
+        }
+        if let Some(volume) = self.volume {
+            config.volume = volume;
+        }
+    }
+}
+
+impl Config {
+    /// Runs `rules` in order against `event`, which they may re-prioritize.
+    /// `cwd` is where the hook ran, for `path` conditions. Returns `None`
+    /// when a rule suppresses the notification.
+    pub fn apply_rules(
+        &self,
+        event: &mut Event,
+        cwd: Option<&Path>,
+        now: DateTime<Utc>,
+    ) -> Option<Config> {
+        let mut config = self.clone();
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.when.matches(event, cwd, now) {
+                continue;
+            }
+            let name = rule
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("rules[{index}]"));
+            tracing::debug!(rule = %name, "rule matched");
+            if rule.then.suppress {
+                tracing::info!(rule = %name, "notification suppressed by rule");
+                return None;
+            }
+            rule.then.apply(event, &mut config);
+            if rule.then.stop {
+                break;
+            }
+        }
+        Some(config)
+    }
+}
+
+/// Compiles a pattern matched against event text, ignoring case unless
+/// `case_sensitive`. The error is the one-line message validation reports.
+pub(crate) fn compile_regex(pattern: &str, case_sensitive: bool) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|err| {
+            let message = err.to_string();
+            format!(
+                "invalid regex: {}",
+                message.split_whitespace().collect::<Vec<_>>().join(" ")
+            )
+        })
+}
+
+pub(crate) fn check(rules: &[Rule], report: &mut ValidationReport) {
+    for (index, rule) in rules.iter().enumerate() {
+        let prefix = format!("rules[{index}]");
+        let when = &rule.when;
+        if let Some(pattern) = &when.summary {
+            if let Err(message) = when.summary_regex(pattern) {
+                report.push(format!("{prefix}.when.summary"), message);
+            }
+        }
+        for pointer in when.context.keys() {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                report.push(
+                    format!("{prefix}.when.context"),
+                    format!("{pointer:?} is not a JSON pointer; use /{pointer}"),
+                );
+            }
+        }
+        if let Some(pattern) = &when.path {
+            if glob_regex(&expand_home(pattern), true).is_none() {
+                report.push(format!("{prefix}.when.path"), "invalid glob");
+            }
+        }
+        if let Some(window) = &when.time {
+            dnd::check_window(window, &format!("{prefix}.when.time"), report);
+        }
+        dnd::check_timezone(
+            when.timezone.as_deref(),
+            &format!("{prefix}.when.timezone"),
+            report,
+        );
+        if let Some(volume) = rule.then.volume {
+            if !(0.0..=1.0).contains(&volume) {
+                report.push(
+                    format!("{prefix}.then.volume"),
+                    "must be between 0.0 and 1.0",
+                );
+            }
+        }
+    }
+}
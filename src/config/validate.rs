@@ -0,0 +1,510 @@
+use super::layers::{Layer, Merger};
+use super::Config;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+const MAX_STRUCTURAL_ISSUES: usize = 64;
+
+/// A single config problem, addressed by its dotted path.
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub path: String,
+    pub message: String,
+    /// Layer or file the offending value came from, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)?;
+        } else {
+            write!(f, "{}: {}", self.path, self.message)?;
+        }
+        if let Some(origin) = &self.origin {
+            write!(f, " ({origin})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(Issue {
+            path: path.into(),
+            message: message.into(),
+            origin: None,
+        });
+    }
+
+    pub fn push_from(
+        &mut self,
+        origin: impl Into<String>,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(Issue {
+            path: path.into(),
+            message: message.into(),
+            origin: Some(origin.into()),
+        });
+    }
+
+    pub fn extend(&mut self, other: ValidationReport) {
+        self.issues.extend(other.issues);
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{self}"))
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, issue) in self.issues.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Unknown,
+}
+
+/// Deserializes `value` as `T`, recording every type error instead of
+/// stopping at the first. Offending values are removed so defaults apply,
+/// and the cleaned-up value is returned.
+pub(crate) fn collect_type_errors<T: DeserializeOwned>(
+    mut value: Value,
+    mut report: impl FnMut(String, String),
+) -> Value {
+    for _ in 0..MAX_STRUCTURAL_ISSUES {
+        let Some((mut segments, message)) = first_error::<T>(&value) else {
+            return value;
+        };
+        resolve_unknown_keys::<T>(&value, &mut segments, &message);
+        report(format_path(&segments), message);
+        if !remove_at(&mut value, &segments) {
+            break;
+        }
+    }
+    value
+}
+
+fn first_error<T: DeserializeOwned>(value: &Value) -> Option<(Vec<Segment>, String)> {
+    use serde_path_to_error::Segment as Seg;
+
+    let err = serde_path_to_error::deserialize::<_, T>(value.clone()).err()?;
+    let segments = err
+        .path()
+        .iter()
+        .map(|segment| match segment {
+            Seg::Map { key } => Segment::Key(key.clone()),
+            Seg::Seq { index } => Segment::Index(*index),
+            Seg::Enum { variant } => Segment::Key(variant.clone()),
+            Seg::Unknown => Segment::Unknown,
+        })
+        .collect();
+    Some((segments, err.into_inner().to_string()))
+}
+
+/// Map keys that aren't plain strings (e.g. `EventType`) show up as unknown
+/// segments. Find the real key by removing each candidate in turn and seeing
+/// which one makes the error go away.
+fn resolve_unknown_keys<T: DeserializeOwned>(
+    value: &Value,
+    segments: &mut [Segment],
+    message: &str,
+) {
+    while let Some(index) = segments.iter().position(|s| *s == Segment::Unknown) {
+        let keys: Vec<String> = match lookup(value, &segments[..index]) {
+            Some(Value::Object(map)) => map.keys().cloned().collect(),
+            _ => return,
+        };
+        let original = format_path(segments);
+        let found = keys.into_iter().find(|key| {
+            let mut candidate = segments.to_vec();
+            candidate[index] = Segment::Key(key.clone());
+            let mut trial = value.clone();
+            if !remove_at(&mut trial, &candidate) {
+                return false;
+            }
+            match first_error::<T>(&trial) {
+                None => true,
+                Some((path, msg)) => format_path(&path) != original || msg != message,
+            }
+        });
+        match found {
+            Some(key) => segments[index] = Segment::Key(key),
+            None => return,
+        }
+    }
+}
+
+fn format_path(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{index}]")),
+            Segment::Unknown => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push('?');
+            }
+        }
+    }
+    out
+}
+
+fn lookup<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |target, segment| match segment {
+            Segment::Key(key) => target.get(key),
+            Segment::Index(index) => target.get(*index),
+            Segment::Unknown => None,
+        })
+}
+
+fn remove_at(value: &mut Value, segments: &[Segment]) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut target = value;
+    for segment in parents {
+        target = match (segment, target) {
+            (Segment::Key(key), Value::Object(map)) => match map.get_mut(key) {
+                Some(child) => child,
+                None => return false,
+            },
+            (Segment::Index(index), Value::Array(items)) => match items.get_mut(*index) {
+                Some(child) => child,
+                None => return false,
+            },
+            _ => return false,
+        };
+    }
+    match (last, target) {
+        (Segment::Key(key), Value::Object(map)) => map.remove(key).is_some(),
+        (Segment::Index(index), Value::Array(items)) if *index < items.len() => {
+            items.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+impl Config {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.validation_report().into_result()
+    }
+
+    /// Checks semantic constraints, reporting every failure.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        if !(0.0..=1.0).contains(&self.volume) {
+            report.push("volume", "must be between 0.0 and 1.0");
+        }
+
+        if let Some(backend) = &self.tts.backend {
+            if !is_known_backend(backend) {
+                report.push("tts.backend", format!("unsupported backend: {backend}"));
+            }
+        }
+        for (index, backend) in self.tts.fallback_backends.iter().enumerate() {
+            if !is_known_backend(backend) {
+                report.push(
+                    format!("tts.fallback_backends[{index}]"),
+                    format!("unsupported backend: {backend}"),
+                );
+            }
+        }
+        let mut timed: Vec<&String> = self.tts.backend_timeouts.keys().collect();
+        timed.sort();
+        for backend in timed {
+            if !is_known_backend(backend) {
+                report.push(
+                    format!("tts.backend_timeouts.{backend}"),
+                    format!("unsupported backend: {backend}"),
+                );
+            }
+        }
+        if self.uses_backend("qwen3-tts") && self.tts.qwen3_tts.model.is_none() {
+            report.push(
+                "tts.qwen3_tts.model",
+                "must be set when the qwen3-tts backend is selected",
+            );
+        }
+        if self.uses_backend("command") && self.tts.command.args.is_empty() {
+            report.push(
+                "tts.command.args",
+                "must be set when the command backend is selected",
+            );
+        }
+        if self.uses_backend("http") {
+            match &self.tts.http.url {
+                None => report.push(
+                    "tts.http.url",
+                    "must be set when the http backend is selected",
+                ),
+                Some(url) => {
+                    if let Err(message) = crate::tts::http::check_url(url) {
+                        report.push("tts.http.url", message);
+                    }
+                }
+            }
+            let format = crate::tts::http::response_format(&self.tts);
+            if let Err(err) = crate::tts::http::check_format(format) {
+                report.push("tts.http.response_format", err.to_string());
+            }
+            for (name, value) in &self.tts.http.headers {
+                if let Err(message) = crate::tts::http::check_header(name, value) {
+                    report.push(format!("tts.http.headers.{name}"), message);
+                }
+            }
+        }
+        let speechd = &self.tts.speech_dispatcher;
+        for (path, value) in [
+            ("tts.speech_dispatcher.rate", speechd.rate),
+            ("tts.speech_dispatcher.volume", speechd.volume),
+        ] {
+            if value.is_some_and(|value| !(-100..=100).contains(&value)) {
+                report.push(path, "must be between -100 and 100");
+            }
+        }
+
+        for (source, overrides) in &self.sources {
+            let prefix = format!("sources.{}", source.as_str());
+            if let Some(volume) = overrides.volume {
+                if !(0.0..=1.0).contains(&volume) {
+                    report.push(format!("{prefix}.volume"), "must be between 0.0 and 1.0");
+                }
+            }
+            if let Some(backend) = &overrides.backend {
+                if !is_known_backend(backend) {
+                    report.push(
+                        format!("{prefix}.backend"),
+                        format!("unsupported backend: {backend}"),
+                    );
+                }
+            }
+        }
+
+        for (index, project) in self.projects.iter().enumerate() {
+            if project.path.is_none() && project.remote.is_none() {
+                report.push(format!("projects[{index}]"), "must set path or remote");
+            }
+            if let Some(volume) = project.volume {
+                if !(0.0..=1.0).contains(&volume) {
+                    report.push(
+                        format!("projects[{index}].volume"),
+                        "must be between 0.0 and 1.0",
+                    );
+                }
+            }
+        }
+
+        for name in self.profile_names() {
+            let prefix = format!("profiles.{name}");
+            let overlay = &self.profiles[name];
+            if !overlay.is_object() {
+                report.push(prefix, "must be a table/object");
+                continue;
+            }
+            collect_type_errors::<Config>(overlay.clone(), |path, message| {
+                report.push(format!("{prefix}.{path}"), message)
+            });
+        }
+
+        super::dnd::check(&self.dnd, &mut report);
+        super::rules::check(&self.rules, &mut report);
+
+        if self.cache_max_mb == Some(0) {
+            report.push("cache_max_mb", "must be greater than 0");
+        }
+
+        if self.cache_max_entries == Some(0) {
+            report.push("cache_max_entries", "must be greater than 0");
+        }
+
+        self.check_offline_voices(&mut report);
+        if self.uses_backend("qwen3-tts") {
+            for (path, message) in crate::tts::qwen3::check_voice(&self.tts) {
+                report.push(path, message);
+            }
+        }
+
+        self.check_templates(&mut report);
+        crate::audio::earcon::check(self, &mut report);
+        crate::voicepack::check_routes(self, &mut report);
+
+        if self.voicepack.enabled {
+            match self.voicepack_manifest_path() {
+                Some(path) if !path.exists() => report.push(
+                    "voicepack.manifest_path",
+                    format!("manifest not found: {}", path.display()),
+                ),
+                Some(_) => {}
+                None => report.push(
+                    "voicepack.manifest_path",
+                    "voicepack enabled but no manifest_path configured",
+                ),
+            }
+        }
+
+        report
+    }
+}
+
+impl Config {
+    /// Whether `name` is the backend or one of its fallbacks.
+    fn uses_backend(&self, name: &str) -> bool {
+        self.tts.backend.as_deref() == Some(name)
+            || self.tts.fallback_backends.iter().any(|b| b == name)
+    }
+
+    /// Checks that every pocket-tts voice a notification can reach loads
+    /// offline: through the backend or its fallbacks, a source's overrides
+    /// or a profile.
+    fn check_offline_voices(&self, report: &mut ValidationReport) {
+        let base = self.offline_voice_issue();
+        if let Some((path, message)) = &base {
+            report.push(*path, message.clone());
+        }
+
+        for (source, overrides) in &self.sources {
+            let Some((path, message)) = self.for_source(*source).offline_voice_issue() else {
+                continue;
+            };
+            let prefix = format!("sources.{}", source.as_str());
+            if path == "tts.voice" && overrides.voice.is_some() {
+                report.push(format!("{prefix}.voice"), message);
+            } else if base.is_none() {
+                report.push(format!("{prefix}.backend"), message);
+            }
+        }
+
+        let Ok(value) = serde_json::to_value(self) else {
+            return;
+        };
+        for name in self.profile_names() {
+            let overlay = &self.profiles[name];
+            if !overlay.is_object() {
+                continue;
+            }
+            let mut merger = Merger::new(value.clone());
+            merger.merge(overlay.clone(), Layer::Profile);
+            // Type errors in the overlay are reported with the profile above.
+            let Ok(config) = serde_json::from_value::<Config>(merger.value) else {
+                continue;
+            };
+            let Some((path, message)) = config.offline_voice_issue() else {
+                continue;
+            };
+            let pointer = format!("/{}", path.replace('.', "/"));
+            if overlay.pointer(&pointer).is_some()
+                || base.as_ref() != Some(&(path, message.clone()))
+            {
+                report.push(format!("profiles.{name}.{path}"), message);
+            }
+        }
+    }
+
+    /// The pocket-tts voice this config would need to download, if it can
+    /// reach pocket-tts without downloads allowed.
+    fn offline_voice_issue(&self) -> Option<(&'static str, String)> {
+        let primary = self.tts.backend.is_none();
+        if !(primary || self.uses_backend("pocket-tts")) || self.tts.allow_downloads {
+            return None;
+        }
+        let (path, spec) = match self.tts.voice.as_deref() {
+            Some(voice) => ("tts.voice", voice),
+            None => (
+                "tts.pocket_tts.voice",
+                self.tts.pocket_tts.voice.as_deref().unwrap_or("alba"),
+            ),
+        };
+        let message = crate::tts::pocket::check_offline_voice(spec).err()?;
+        Some((path, message))
+    }
+
+    fn check_templates(&self, report: &mut ValidationReport) {
+        let mut templates = Vec::new();
+        for (event_type, event_config) in &self.events {
+            if let Some(template) = &event_config.template {
+                templates.push((format!("events.{}.template", event_type.as_str()), template));
+            }
+        }
+        for (source, overrides) in &self.sources {
+            for (event_type, event_override) in &overrides.events {
+                if let Some(template) = &event_override.template {
+                    let path = format!(
+                        "sources.{}.events.{}.template",
+                        source.as_str(),
+                        event_type.as_str()
+                    );
+                    templates.push((path, template));
+                }
+            }
+        }
+        for (index, project) in self.projects.iter().enumerate() {
+            for (event_type, event_override) in &project.events {
+                if let Some(template) = &event_override.template {
+                    let path = format!("projects[{index}].events.{}.template", event_type.as_str());
+                    templates.push((path, template));
+                }
+            }
+        }
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if let Some(template) = &rule.then.template {
+                templates.push((format!("rules[{index}].then.template"), template));
+            }
+        }
+
+        templates.sort();
+        for (path, template) in templates {
+            if let Err(message) = crate::tts::broker::check_template(template) {
+                report.push(path, message);
+            }
+        }
+    }
+}
+
+fn is_known_backend(name: &str) -> bool {
+    matches!(
+        name,
+        "pocket-tts" | "qwen3-tts" | "command" | "http" | "speech-dispatcher"
+    )
+}
@@ -0,0 +1,162 @@
+use crate::config::Config;
+use crate::events::Event;
+use crate::runtime;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const SOCKET_NAME: &str = "daemon.sock";
+/// How long `notify` waits on the daemon before delivering the event itself.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One notification handed from `notify` to the daemon. The config is
+/// resolved by the caller, since project and env layers depend on where the
+/// hook ran.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub event: Event,
+    pub config: Config,
+    #[serde(default)]
+    pub backend: Option<String>,
+    /// Where `notify` ran, for project overrides and `path` rules when the
+    /// hook payload doesn't report a `cwd`. The daemon's own cwd is
+    /// unrelated.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// The env and CLI values behind `config`, which outrank per-source,
+    /// per-project and rule overrides.
+    #[serde(default)]
+    pub pinned: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Reply {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+pub fn socket_path(runtime_dir: &Path) -> PathBuf {
+    runtime_dir.join(SOCKET_NAME)
+}
+
+/// Whether a daemon is accepting connections in `runtime_dir`.
+pub fn is_running(runtime_dir: &Path) -> bool {
+    UnixStream::connect(socket_path(runtime_dir)).is_ok()
+}
+
+/// Hands `request` to the daemon in its config's runtime dir. Returns false
+/// when none answered, so the caller should deliver the event itself.
+pub fn forward(request: &Request) -> bool {
+    match try_forward(request) {
+        Ok(()) => {
+            tracing::debug!("event forwarded to daemon");
+            true
+        }
+        Err(err) => {
+            tracing::debug!(error = ?err, "daemon unavailable; delivering in process");
+            false
+        }
+    }
+}
+
+fn try_forward(request: &Request) -> anyhow::Result<()> {
+    let runtime_dir = request.config.default_runtime_dir();
+    runtime::ensure_dir(&runtime_dir)?;
+    let path = socket_path(&runtime_dir);
+    let mut stream = UnixStream::connect(path).context("connect to daemon")?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = serde_json::to_vec(request).context("serialize request")?;
+    line.push(b'\n');
+    stream.write_all(&line).context("send request")?;
+
+    let mut raw = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut raw)
+        .context("read daemon reply")?;
+    let reply: Reply = serde_json::from_str(&raw).context("parse daemon reply")?;
+    if !reply.ok {
+        anyhow::bail!("daemon rejected event: {}", reply.error.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Listens on the socket in `runtime_dir` until killed, acknowledging each
+/// request before passing it to `deliver` on its own thread.
+pub fn serve<F>(runtime_dir: &Path, deliver: F) -> anyhow::Result<()>
+where
+    F: Fn(Request) -> anyhow::Result<()> + Send + Sync + Clone + 'static,
+{
+    let path = socket_path(runtime_dir);
+    if is_running(runtime_dir) {
+        anyhow::bail!("daemon already running at {}", path.display());
+    }
+    runtime::ensure_dir(runtime_dir)?;
+    // Left behind by a daemon that didn't shut down cleanly.
+    if path.exists() {
+        std::fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(&path).with_context(|| format!("bind {}", path.display()))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .context("restrict socket permissions")?;
+    tracing::info!(socket = %path.display(), "daemon listening");
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!(error = ?err, "daemon accept failed");
+                continue;
+            }
+        };
+        let deliver = deliver.clone();
+        thread::spawn(move || {
+            let Some(request) = accept(stream) else {
+                return;
+            };
+            let event = request.event.event_type.as_str();
+            if let Err(err) = deliver(request) {
+                tracing::warn!(event, error = ?err, "daemon delivery failed");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn accept(mut stream: UnixStream) -> Option<Request> {
+    let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+    let mut raw = String::new();
+    if let Err(err) = BufReader::new(&stream).read_line(&mut raw) {
+        tracing::debug!(error = ?err, "daemon read failed");
+        return None;
+    }
+    let (request, reply) = match serde_json::from_str::<Request>(&raw) {
+        Ok(request) => (
+            Some(request),
+            Reply {
+                ok: true,
+                error: None,
+            },
+        ),
+        Err(err) => (
+            None,
+            Reply {
+                ok: false,
+                error: Some(err.to_string()),
+            },
+        ),
+    };
+    let mut line = serde_json::to_vec(&reply).ok()?;
+    line.push(b'\n');
+    // Only deliver once the client knows not to.
+    stream.write_all(&line).ok()?;
+    request
+}
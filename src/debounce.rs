@@ -0,0 +1,101 @@
+use crate::config::DebounceConfig;
+use crate::events::Event;
+use crate::runtime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const STATE_NAME: &str = "debounce";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Play,
+    /// Same event from the same session fired within the debounce window.
+    Debounced,
+    /// Same event type already played recently for another session.
+    Coalesced,
+}
+
+/// When each key last played, and for how long that blocks a repeat.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    last: HashMap<String, Mark>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Mark {
+    /// Unix milliseconds.
+    at: i64,
+    window_ms: u64,
+}
+
+impl Mark {
+    /// Events are timestamped when created but reach the lock in any order,
+    /// so an event from just before the mark is as much a repeat as one from
+    /// just after.
+    fn blocks(&self, now: i64) -> bool {
+        (now - self.at).unsigned_abs() < self.window_ms
+    }
+}
+
+/// Decides whether `event` should play, recording it if so. State lives in
+/// a lock-protected file in `runtime_dir` so concurrent `notify` processes
+/// agree. Errors fall back to playing.
+pub fn check(event: &Event, config: &DebounceConfig, runtime_dir: &Path) -> Verdict {
+    if !config.enabled {
+        return Verdict::Play;
+    }
+
+    let now = event.timestamp.timestamp_millis();
+    let window = config
+        .events
+        .get(&event.event_type)
+        .copied()
+        .unwrap_or(config.window_ms);
+    let coalesces = config.coalesce.contains(&event.event_type);
+    let event_name = event.event_type.as_str();
+    let session_key = format!("{event_name}|{}", event.session_id().unwrap_or("-"));
+    let type_key = format!("{event_name}|*");
+
+    let result = runtime::update(runtime_dir, STATE_NAME, |state: &mut State| {
+        state.last.retain(|_, mark| mark.blocks(now));
+
+        let recent = |key: &str| state.last.get(key).is_some_and(|mark| mark.blocks(now));
+        let verdict = if recent(&session_key) {
+            Verdict::Debounced
+        } else if coalesces && recent(&type_key) {
+            Verdict::Coalesced
+        } else {
+            Verdict::Play
+        };
+
+        if verdict == Verdict::Play {
+            state.last.insert(
+                session_key.clone(),
+                Mark {
+                    at: now,
+                    window_ms: window,
+                },
+            );
+            if coalesces {
+                state.last.insert(
+                    type_key.clone(),
+                    Mark {
+                        at: now,
+                        window_ms: config.coalesce_ms,
+                    },
+                );
+            }
+        }
+        verdict
+    });
+
+    match result {
+        Ok(verdict) => verdict,
+        Err(err) => {
+            tracing::debug!(error = ?err, "debounce state unavailable; playing");
+            Verdict::Play
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use chrono::{DateTime, Utc};
 use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, ValueEnum,
+)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EventType {
     #[value(alias = "AGENT_YIELD", alias = "agent_yield")]
@@ -14,6 +18,21 @@ pub enum EventType {
 }
 
 impl EventType {
+    pub const ALL: [EventType; 3] = [
+        EventType::AgentYield,
+        EventType::DecisionRequired,
+        EventType::ErrorRetry,
+    ];
+
+    /// Config key, as used in `events.<KEY>`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventType::AgentYield => "AGENT_YIELD",
+            EventType::DecisionRequired => "DECISION_REQUIRED",
+            EventType::ErrorRetry => "ERROR_RETRY",
+        }
+    }
+
     pub fn default_template(self) -> &'static str {
         match self {
             EventType::AgentYield => "Ready.",
@@ -23,7 +42,9 @@ impl EventType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, ValueEnum,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Source {
     Claude,
@@ -32,7 +53,19 @@ pub enum Source {
     OpenCode,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Claude => "claude",
+            Source::Codex => "codex",
+            Source::OpenCode => "opencode",
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
     Low,
@@ -40,6 +73,16 @@ pub enum Priority {
     High,
 }
 
+impl Priority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub event_type: EventType,
@@ -70,4 +113,24 @@ impl Event {
             priority,
         }
     }
+
+    /// Session or thread id from the hook payload, used to tell parallel
+    /// agents apart.
+    pub fn session_id(&self) -> Option<&str> {
+        let context = self.context.as_ref()?;
+        ["session_id", "thread-id", "thread_id"]
+            .iter()
+            .find_map(|key| context.get(*key).and_then(|v| v.as_str()))
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Working directory reported by the hook payload.
+    pub fn working_dir(&self) -> Option<PathBuf> {
+        self.context
+            .as_ref()
+            .and_then(|ctx| ctx.get("cwd"))
+            .and_then(|v| v.as_str())
+            .filter(|cwd| !cwd.is_empty())
+            .map(PathBuf::from)
+    }
 }
@@ -1,40 +1,61 @@
 pub mod adapters;
+pub mod assets;
 pub mod audio;
 pub mod cli;
 pub mod config;
+pub mod daemon;
+pub mod debounce;
 pub mod events;
+pub mod runtime;
 pub mod system;
 pub mod tts;
 pub mod voicepack;
 
 use anyhow::Context;
+use audio::renderer::Playback;
 use cli::{Cli, Commands};
-use events::{Event, Source};
+use events::{Event, Priority, Source};
 use std::io::Read;
 
 pub fn run(cli: Cli) -> anyhow::Result<()> {
-    setup_tracing(cli.verbose);
+    // The detached worker's stderr is its log file.
+    setup_tracing(
+        cli.verbose,
+        matches!(cli.command, Commands::InternalWorker(_)),
+    );
+    let options = config::LoadOptions {
+        overrides: cli.overrides,
+        profile: cli.profile,
+        ..Default::default()
+    };
 
     match cli.command {
-        Commands::Notify(args) => notify(args),
-        Commands::SystemInfo(args) => system_info(args),
-        Commands::Models(args) => models(args),
-        Commands::TestTts(args) => test_tts(args),
-        Commands::Config(args) => config_cmd(args),
-        Commands::InternalSynthesize(args) => internal_synthesize(args),
+        Commands::Notify(args) => notify(args, &options),
+        Commands::SystemInfo(args) => system_info(args, &options),
+        Commands::Models(args) => models(args, &options),
+        Commands::TestTts(args) => test_tts(args, &options),
+        Commands::Config(args) => config_cmd(args, &options),
+        Commands::Profile(args) => profile_cmd(args, &options),
+        Commands::Daemon(args) => daemon_cmd(args, &options),
+        Commands::Assets(args) => assets_cmd(args, &options),
+        Commands::InternalWorker(args) => tts::worker::serve(&args.runtime_dir),
     }
 }
 
-fn setup_tracing(verbose: bool) {
+fn setup_tracing(verbose: bool, to_stderr: bool) {
     let filter = if verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
+    let builder = tracing_subscriber::fmt()
         .with_env_filter(filter)
-        .with_target(false)
-        .init();
+        .with_target(false);
+    if to_stderr {
+        builder.with_writer(std::io::stderr).init();
+    } else {
+        builder.init();
+    }
 }
 
-fn notify(args: cli::NotifyArgs) -> anyhow::Result<()> {
-    let config = config::Config::load().context("load config")?;
+fn notify(args: cli::NotifyArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    let (config, resolution) = config::Config::resolve(options).context("load config")?;
 
     let mut payload_text = args.payload.clone();
     let event_type = match args.event {
@@ -74,10 +95,62 @@ fn notify(args: cli::NotifyArgs) -> anyhow::Result<()> {
             .and_then(|payload| adapters::extract_summary(args.source, payload))
     });
 
-    let event = Event::with_summary(event_type, args.source, summary);
+    let mut event = Event::with_summary(event_type, args.source, summary);
+    event.context = payload_text
+        .as_deref()
+        .and_then(|payload| serde_json::from_str(payload).ok());
+
+    let request = daemon::Request {
+        event,
+        config,
+        backend: args.backend,
+        cwd: std::env::current_dir().ok(),
+        pinned: resolution.pinned,
+    };
+    if daemon::forward(&request) {
+        return Ok(());
+    }
+    deliver(request)
+}
+
+/// Runs a parsed event through policy and playback, in `notify` or the
+/// daemon.
+fn deliver(request: daemon::Request) -> anyhow::Result<()> {
+    let daemon::Request {
+        mut event,
+        config,
+        backend,
+        cwd,
+        pinned,
+    } = request;
+    let cwd = event.working_dir().or(cwd);
+    let mut config = config.for_source(event.source);
+    if let Some(cwd) = &cwd {
+        config = config.for_project(cwd);
+    }
+    let now = event.timestamp;
+    let Some(config) = config.apply_rules(&mut event, cwd.as_deref(), now) else {
+        return Ok(());
+    };
+    let config = config.pin(&pinned)?;
+    let Some(config) = config.for_quiet_hours(&event, now) else {
+        tracing::info!(event = event.event_type.as_str(), "muted by quiet hours");
+        return Ok(());
+    };
+    match debounce::check(&event, &config.debounce, &config.default_runtime_dir()) {
+        debounce::Verdict::Play => {}
+        verdict => {
+            tracing::info!(
+                event = event.event_type.as_str(),
+                ?verdict,
+                "skipping burst"
+            );
+            return Ok(());
+        }
+    }
 
     if let Ok(Some(audio)) = voicepack::select_audio(&event, &config) {
-        if let Err(err) = tts::play_audio(&audio, config.volume) {
+        if let Err(err) = tts::play_audio(&audio, &Playback::new(&config, event.priority)) {
             tracing::warn!(error = ?err, "voicepack playback failed; falling back");
         } else {
             return Ok(());
@@ -87,22 +160,29 @@ fn notify(args: cli::NotifyArgs) -> anyhow::Result<()> {
     let text = tts::broker::get_text_for_event(&event, &config);
 
     if let Some(text) = text {
-        if let Err(err) = tts::synthesize_and_play(&text, &config, &args.backend) {
+        if let Err(err) = tts::synthesize_and_play(&text, &config, &backend, event.priority) {
             tracing::warn!(error = ?err, "tts failed; trying earcon");
-            audio::earcon::play_for_event(event.event_type, &config)?;
+            audio::earcon::play_fallback(event.event_type, &config, event.priority)?;
         }
         return Ok(());
     }
 
     if audio::earcon::should_play(event.event_type, &config) {
-        audio::earcon::play_for_event(event.event_type, &config)?;
+        audio::earcon::play_for_event(event.event_type, &config, event.priority)?;
     }
 
     Ok(())
 }
 
-fn system_info(args: cli::SystemInfoArgs) -> anyhow::Result<()> {
-    let info = system::detect();
+fn system_info(args: cli::SystemInfoArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    let mut info = system::detect();
+    match config::Config::load_with(options) {
+        Ok(config) => match config.dnd.state_at(chrono::Utc::now()) {
+            Ok(state) => info.dnd = Some(state),
+            Err(message) => tracing::warn!(%message, "cannot evaluate quiet hours"),
+        },
+        Err(err) => tracing::warn!(error = ?err, "config did not load; skipping quiet hours"),
+    }
     if args.json {
         println!("{}", serde_json::to_string_pretty(&info)?);
         return Ok(());
@@ -116,12 +196,25 @@ fn system_info(args: cli::SystemInfoArgs) -> anyhow::Result<()> {
     if let Some(recommended) = info.recommended_backends {
         println!("Recommended backends: {}", recommended.join(", "));
     }
+    if let Some(dnd) = &info.dnd {
+        match &dnd.until {
+            Some(until) => println!(
+                "Do not disturb: active until {until} ({}, now {})",
+                dnd.timezone, dnd.local_time
+            ),
+            None => println!(
+                "Do not disturb: inactive ({}, now {})",
+                dnd.timezone, dnd.local_time
+            ),
+        }
+    }
 
     Ok(())
 }
 
-fn models(args: cli::ModelsArgs) -> anyhow::Result<()> {
-    let info = tts::models_info()?;
+fn models(args: cli::ModelsArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    let config = config::Config::load_with(options)?;
+    let info = tts::models_info(&config)?;
     if args.json {
         println!("{}", serde_json::to_string_pretty(&info)?);
         return Ok(());
@@ -144,8 +237,8 @@ fn models(args: cli::ModelsArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn test_tts(args: cli::TestTtsArgs) -> anyhow::Result<()> {
-    let mut config = config::Config::load().context("load config")?;
+fn test_tts(args: cli::TestTtsArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    let mut config = config::Config::load_with(options).context("load config")?;
     if let Some(voice) = args.voice {
         config.tts.voice = Some(voice);
     }
@@ -154,52 +247,224 @@ fn test_tts(args: cli::TestTtsArgs) -> anyhow::Result<()> {
     }
     let text = args.text.unwrap_or_else(|| "Hello world".to_string());
 
-    let audio = tts::synthesize(&text, &config, &args.backend).context("tts synthesis")?;
-
-    if let Some(path) = args.output {
-        std::fs::write(path, &audio).context("write output")?;
-    }
+    // Without a file to write, stream it like a notification would, which
+    // also covers backends that speak directly.
+    let Some(path) = args.output else {
+        return tts::synthesize_and_play(&text, &config, &args.backend, Priority::Normal)
+            .context("tts synthesis");
+    };
 
-    tts::play_audio(&audio, config.volume)?;
+    let audio = tts::synthesize(&text, &config, &args.backend).context("tts synthesis")?;
+    std::fs::write(path, &audio).context("write output")?;
+    tts::play_audio(&audio, &Playback::new(&config, Priority::Normal))?;
 
     Ok(())
 }
 
-fn config_cmd(args: cli::ConfigArgs) -> anyhow::Result<()> {
+fn config_cmd(args: cli::ConfigArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    if let Some(command) = args.command {
+        return match command {
+            cli::ConfigCommand::Schema => {
+                let schema = schemars::schema_for!(config::Config);
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+                Ok(())
+            }
+            cli::ConfigCommand::Get { key } => {
+                match config::Config::get_key(options, &key)? {
+                    serde_json::Value::String(value) => println!("{value}"),
+                    value => println!("{}", serde_json::to_string_pretty(&value)?),
+                }
+                Ok(())
+            }
+            cli::ConfigCommand::Set { key, value, target } => {
+                let path = config::Config::set_key(options, target.layer(), &key, &value)?;
+                println!("Set {key} in {}", path.display());
+                Ok(())
+            }
+            cli::ConfigCommand::Unset { key, target } => {
+                let path = config::Config::unset_key(options, target.layer(), &key)?;
+                println!("Unset {key} in {}", path.display());
+                Ok(())
+            }
+            cli::ConfigCommand::Migrate { file, target } => {
+                let path = match file {
+                    Some(path) => path,
+                    None => config::Config::layer_path(options, target.layer())?,
+                };
+                match config::Config::migrate_file(&path)? {
+                    Some(migration) => {
+                        println!(
+                            "Migrated {} from schema v{} to v{}",
+                            migration.path.display(),
+                            migration.from,
+                            config::SCHEMA_VERSION
+                        );
+                        if let Some(backup) = migration.backup {
+                            println!("Backup: {}", backup.display());
+                        }
+                    }
+                    None => println!(
+                        "{} is already at schema v{}",
+                        path.display(),
+                        config::SCHEMA_VERSION
+                    ),
+                }
+                Ok(())
+            }
+        };
+    }
+
     if args.init {
-        let path = config::Config::init_default()?;
+        let path = config::Config::init_default(options, args.format, args.project)?;
         println!("Initialized config at {}", path.display());
         return Ok(());
     }
 
     if args.show {
-        let config = config::Config::load()?;
+        let config = config::Config::load_with(options)?;
         println!("{}", serde_json::to_string_pretty(&config)?);
         return Ok(());
     }
 
     if args.validate {
-        let config = config::Config::load()?;
-        config.validate()?;
-        println!("Config OK");
+        let report = config::Config::check(options);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if report.is_ok() {
+                return Ok(());
+            }
+            anyhow::bail!("config has {} problem(s)", report.issues.len());
+        }
+        if report.is_ok() {
+            println!("Config OK");
+            return Ok(());
+        }
+        for issue in &report.issues {
+            println!("{issue}");
+        }
+        anyhow::bail!("config has {} problem(s)", report.issues.len());
+    }
+
+    let (config, resolution) = config::Config::resolve(options)?;
+
+    if args.explain {
+        // Round-trip through text so f32 fields print as written (0.8, not 0.800000011).
+        let value: serde_json::Value = serde_json::from_str(&serde_json::to_string(&config)?)?;
+        let mut leaves = Vec::new();
+        flatten_json(&value, String::new(), &mut leaves);
+        for (key, value) in leaves {
+            let layer = resolution.layer_for(&key);
+            match resolution.path_for(layer) {
+                Some(path) => println!("{key} = {value}  ({layer}: {})", path.display()),
+                None => println!("{key} = {value}  ({layer})"),
+            }
+        }
         return Ok(());
     }
 
-    let path = config::Config::default_path()?;
-    println!("{}", path.display());
+    if resolution.files.is_empty() {
+        println!("(built-in defaults)");
+    }
+    for (layer, path) in &resolution.files {
+        println!("{layer}: {}", path.display());
+    }
+    if let Some(discovery) = &resolution.discovery {
+        println!("reason: {discovery}");
+    }
+    if let Some(profile) = &resolution.profile {
+        println!("profile: {} (from {})", profile.name, profile.source);
+    }
+    Ok(())
+}
+
+fn profile_cmd(args: cli::ProfileArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    match args.command {
+        cli::ProfileCommand::List => {
+            let (config, resolution) = config::Config::resolve(options)?;
+            let active = resolution.profile.as_ref().map(|p| p.name.as_str());
+            for name in config.profile_names() {
+                let marker = if Some(name) == active { "*" } else { " " };
+                println!("{marker} {name}");
+            }
+            if let Some(profile) = &resolution.profile {
+                println!("(active via {})", profile.source);
+            }
+        }
+        cli::ProfileCommand::Use { name } => {
+            let config = config::Config::load_with(options)?;
+            if !config.profiles.contains_key(&name) {
+                anyhow::bail!(
+                    "unknown profile {name}; available: {}",
+                    config.profile_names().join(", ")
+                );
+            }
+            config::save_active_profile(options, Some(&name))?;
+            println!("Using profile {name}");
+            if options.var(config::PROFILE_ENV).is_some() {
+                println!("Note: {} is set and takes precedence", config::PROFILE_ENV);
+            }
+        }
+        cli::ProfileCommand::Clear => {
+            config::save_active_profile(options, None)?;
+            println!("Cleared saved profile");
+        }
+    }
     Ok(())
 }
 
-fn internal_synthesize(args: cli::InternalSynthesizeArgs) -> anyhow::Result<()> {
-    let raw = read_stdin_bytes().context("read config from stdin")?;
-    if raw.is_empty() {
-        anyhow::bail!("internal synth expects config JSON on stdin");
+fn flatten_json(value: &serde_json::Value, prefix: String, out: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_json(child, path, out);
+            }
+        }
+        _ => out.push((prefix, value.to_string())),
+    }
+}
+
+fn daemon_cmd(args: cli::DaemonArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    tts::resident::enable();
+    let config = config::Config::load_with(options).context("load config")?;
+    let runtime_dir = config.default_runtime_dir();
+    if !args.no_preload {
+        std::thread::spawn(move || match tts::preload(&config, &None) {
+            Ok(()) => tracing::info!("tts model loaded"),
+            Err(err) => tracing::warn!(error = ?err, "tts preload failed"),
+        });
+    }
+    daemon::serve(&runtime_dir, deliver)
+}
+
+fn assets_cmd(args: cli::AssetsArgs, options: &config::LoadOptions) -> anyhow::Result<()> {
+    match args.command {
+        cli::AssetsCommand::Install { dir, force } => {
+            let dir = match dir {
+                Some(dir) => dir,
+                None => config::Config::load_with(options)
+                    .context("load config")?
+                    .default_data_dir()
+                    .context("unable to resolve home directory")?,
+            };
+            let report = assets::install(&dir, force)?;
+            println!(
+                "Installed {} files into {}",
+                report.written.len(),
+                dir.display()
+            );
+            if !report.skipped.is_empty() {
+                println!(
+                    "Kept {} existing files (use --force to overwrite)",
+                    report.skipped.len()
+                );
+            }
+        }
     }
-    let config: config::Config = serde_json::from_slice(&raw).context("parse config JSON")?;
-    let audio = tts::synthesize_in_process(&args.text, &config, &args.backend)?;
-    let mut stdout = std::io::stdout();
-    std::io::Write::write_all(&mut stdout, &audio).context("write audio to stdout")?;
-    std::io::Write::flush(&mut stdout)?;
     Ok(())
 }
 
@@ -217,10 +482,3 @@ fn read_stdin_json() -> Option<String> {
         None
     }
 }
-
-fn read_stdin_bytes() -> anyhow::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    let mut stdin = std::io::stdin();
-    stdin.read_to_end(&mut buf)?;
-    Ok(buf)
-}
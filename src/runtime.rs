@@ -0,0 +1,108 @@
+use anyhow::{bail, Context};
+use directories::BaseDirs;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+/// Default directory for per-user state shared between `agent-chime`
+/// processes: `$XDG_RUNTIME_DIR/agent-chime`, else a per-user dir under the
+/// temp dir. `runtime_dir` in the config replaces it.
+pub fn default_dir() -> PathBuf {
+    if let Some(dir) = BaseDirs::new().and_then(|base| base.runtime_dir().map(|d| d.to_path_buf()))
+    {
+        return dir.join("agent-chime");
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+    std::env::temp_dir().join(format!("agent-chime-{user}"))
+}
+
+/// Creates `dir` if needed, readable by this user alone, and refuses one
+/// that another user owns or can get into. The default under the temp dir is
+/// a predictable path, and the sockets in it are sent the whole config, HTTP
+/// headers included.
+pub fn ensure_dir(dir: &Path) -> anyhow::Result<()> {
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .with_context(|| format!("create {}", dir.display()))?;
+    let metadata = fs::symlink_metadata(dir).with_context(|| format!("stat {}", dir.display()))?;
+    if !metadata.is_dir() {
+        bail!("runtime dir {} is not a directory", dir.display());
+    }
+    // SAFETY: geteuid has no preconditions and always succeeds.
+    let uid = unsafe { libc::geteuid() };
+    if metadata.uid() != uid {
+        bail!(
+            "runtime dir {} belongs to uid {}, not {uid}; refusing to use it",
+            dir.display(),
+            metadata.uid()
+        );
+    }
+    if metadata.mode() & 0o077 != 0 {
+        bail!(
+            "runtime dir {} is open to other users (mode {:o}); run `chmod 700 {}`",
+            dir.display(),
+            metadata.mode() & 0o777,
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Opens (creating if needed) a file in the runtime dir.
+pub fn open(dir: &Path, name: &str) -> anyhow::Result<File> {
+    ensure_dir(dir)?;
+    let path = dir.join(name);
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("open {}", path.display()))
+}
+
+/// Runs `f` on the JSON state in `<name>.json` under an exclusive lock and
+/// writes the result back. The lock is an OS file lock, so it is released
+/// even if the holder crashes. Unreadable state starts over from default.
+pub fn update<T, R>(dir: &Path, name: &str, f: impl FnOnce(&mut T) -> R) -> anyhow::Result<R>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    let mut file = open(dir, &format!("{name}.json"))?;
+    file.lock().context("lock runtime state")?;
+
+    let mut raw = String::new();
+    file.read_to_string(&mut raw)
+        .context("read runtime state")?;
+    let mut state: T = serde_json::from_str(&raw).unwrap_or_default();
+    let result = f(&mut state);
+
+    let bytes = serde_json::to_vec(&state).context("serialize runtime state")?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&bytes).context("write runtime state")?;
+    Ok(result)
+}
+
+/// Reads `<name>.json` under a shared lock.
+pub fn read<T>(dir: &Path, name: &str) -> T
+where
+    T: Default + DeserializeOwned,
+{
+    let Ok(mut file) = File::open(dir.join(format!("{name}.json"))) else {
+        return T::default();
+    };
+    if file.lock_shared().is_err() {
+        return T::default();
+    }
+    let mut raw = String::new();
+    if file.read_to_string(&mut raw).is_err() {
+        return T::default();
+    }
+    serde_json::from_str(&raw).unwrap_or_default()
+}
@@ -1,3 +1,4 @@
+use crate::config::DndState;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -6,6 +7,9 @@ pub struct SystemInfo {
     pub arch: String,
     pub cpu_cores: Option<usize>,
     pub recommended_backends: Option<Vec<String>>,
+    /// Quiet-hours state from the effective config, if it loads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dnd: Option<DndState>,
 }
 
 pub fn detect() -> SystemInfo {
@@ -26,5 +30,6 @@ pub fn detect() -> SystemInfo {
         arch,
         cpu_cores,
         recommended_backends,
+        dnd: None,
     }
 }
@@ -1,5 +1,15 @@
 use crate::config::{Config, Mode};
-use crate::events::Event;
+use crate::events::{Event, EventType, Source};
+use std::time::Duration;
+
+const DEFAULT_BREAK_MS: u64 = 250;
+const MAX_BREAK_MS: u64 = 5000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Break(Duration),
+}
 
 pub fn get_text_for_event(event: &Event, config: &Config) -> Option<String> {
     let event_config = config.events.get(&event.event_type)?;
@@ -8,10 +18,175 @@ pub fn get_text_for_event(event: &Event, config: &Config) -> Option<String> {
     }
 
     match event_config.mode {
-        Mode::Tts => event_config
-            .template
-            .clone()
-            .or_else(|| Some(event.event_type.default_template().to_string())),
+        Mode::Tts => {
+            let template = event_config
+                .template
+                .clone()
+                .unwrap_or_else(|| event.event_type.default_template().to_string());
+            Some(render_template(&template, event))
+        }
         Mode::Earcon | Mode::Silent => None,
     }
 }
+
+/// Fills in `{summary}` and `{source}`. Their values are escaped, so an
+/// agent's summary is spoken as written and can't add markup of its own.
+pub fn render_template(template: &str, event: &Event) -> String {
+    let summary = event.summary.as_deref().unwrap_or("").trim();
+    let placeholders = [("{summary}", summary), ("{source}", event.source.as_str())];
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                rendered.push_str(&escape(value));
+                rest = &rest[name.len()..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered.trim().to_string()
+}
+
+const ENTITIES: [(&str, char); 3] = [("&lt;", '<'), ("&gt;", '>'), ("&amp;", '&')];
+
+/// Escapes text so `parse_markup` reads it back unchanged.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match ENTITIES.iter().find(|(entity, _)| rest.starts_with(entity)) {
+            Some((entity, c)) => {
+                unescaped.push(*c);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Checks that a template only uses known placeholders and leaves something
+/// to speak once markup is stripped.
+pub fn check_template(template: &str) -> Result<(), String> {
+    let probe = Event::with_summary(
+        EventType::AgentYield,
+        Source::Claude,
+        Some("summary".to_string()),
+    );
+    let rendered = render_template(template, &probe);
+
+    if let Some(start) = rendered.find('{') {
+        if let Some(len) = rendered[start..].find('}') {
+            let name = &rendered[start + 1..start + len];
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!(
+                    "unknown placeholder {{{name}}}; supported: {{summary}}, {{source}}"
+                ));
+            }
+        }
+    }
+
+    let segments = parse_markup(&rendered);
+    if !segments.iter().any(|seg| matches!(seg, Segment::Text(_))) {
+        return Err("template renders to no speakable text".to_string());
+    }
+    Ok(())
+}
+
+/// Splits text into spoken segments and pauses.
+///
+/// Supports `<break 300ms/>`, `<break time="1.5s"/>` and a bare `<break/>`.
+/// Any other tag is dropped while its surrounding text is kept. `&lt;`,
+/// `&gt;` and `&amp;` stand for the literal characters.
+pub fn parse_markup(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        current.push_str(&rest[..start]);
+        let tag = &rest[start + 1..start + len];
+        rest = &rest[start + len + 1..];
+
+        match parse_break(tag) {
+            Some(duration) => {
+                push_text(&mut segments, &mut current);
+                segments.push(Segment::Break(duration));
+            }
+            None => tracing::debug!(tag = %tag, "ignoring unsupported markup tag"),
+        }
+    }
+
+    current.push_str(rest);
+    push_text(&mut segments, &mut current);
+    segments
+}
+
+pub fn has_markup(segments: &[Segment]) -> bool {
+    segments.iter().any(|seg| matches!(seg, Segment::Break(_)))
+}
+
+fn push_text(segments: &mut Vec<Segment>, current: &mut String) {
+    let text = unescape(&current.split_whitespace().collect::<Vec<_>>().join(" "));
+    current.clear();
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+}
+
+fn parse_break(tag: &str) -> Option<Duration> {
+    let tag = tag.trim().trim_end_matches('/').trim();
+    let mut parts = tag.split_whitespace();
+    if !parts.next()?.eq_ignore_ascii_case("break") {
+        return None;
+    }
+
+    let ms = match parts.next() {
+        Some(arg) => {
+            let value = arg
+                .strip_prefix("time=")
+                .unwrap_or(arg)
+                .trim_matches(|c| c == '"' || c == '\'');
+            parse_duration_ms(value).unwrap_or(DEFAULT_BREAK_MS)
+        }
+        None => DEFAULT_BREAK_MS,
+    };
+
+    Some(Duration::from_millis(ms.min(MAX_BREAK_MS)))
+}
+
+fn parse_duration_ms(value: &str) -> Option<u64> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms.trim().parse::<u64>().ok();
+    }
+    if let Some(secs) = value.strip_suffix('s') {
+        let secs = secs.trim().parse::<f64>().ok()?;
+        if secs.is_sign_negative() || !secs.is_finite() {
+            return None;
+        }
+        return Some((secs * 1000.0).round() as u64);
+    }
+    value.parse::<u64>().ok()
+}
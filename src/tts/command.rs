@@ -0,0 +1,93 @@
+use crate::audio::wav;
+use crate::config::TtsConfig;
+use crate::tts::provider::TtsBackend;
+use anyhow::{bail, Context};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Runs `tts.command.args`, e.g. Piper or espeak-ng, for each clip.
+pub struct CommandBackend;
+
+impl CommandBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CommandBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TtsBackend for CommandBackend {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn synthesize(
+        &self,
+        text: &str,
+        config: &TtsConfig,
+        _timeout: Duration,
+    ) -> anyhow::Result<Vec<u8>> {
+        let Some((program, _)) = config.command.args.split_first() else {
+            bail!("tts.command.args is empty");
+        };
+        let text_in_args = config.command.args.iter().any(|arg| arg.contains("{text}"));
+        let out_in_args = config.command.args.iter().any(|arg| arg.contains("{out}"));
+
+        let dir = tempfile::tempdir().context("create command output dir")?;
+        let out = dir.path().join("speech.wav");
+        let out_arg = out.to_string_lossy();
+        let args: Vec<String> = config.command.args[1..]
+            .iter()
+            .map(|arg| arg.replace("{out}", &out_arg).replace("{text}", text))
+            .collect();
+
+        let mut child = Command::new(program)
+            .args(&args)
+            .stdin(if text_in_args {
+                Stdio::null()
+            } else {
+                Stdio::piped()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("run {program}"))?;
+
+        // Written from a thread so a program that prints before it has read
+        // all of stdin can't deadlock us.
+        let writer = child.stdin.take().map(|mut stdin| {
+            let text = text.to_string();
+            thread::spawn(move || stdin.write_all(text.as_bytes()))
+        });
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("wait for {program}"))?;
+        if let Some(writer) = writer {
+            // A program that exits without reading stdin is judged by its
+            // status and output, not by the broken pipe.
+            let _ = writer.join();
+        }
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("{program} failed ({}): {}", output.status, stderr.trim());
+        }
+        let audio = if out_in_args {
+            std::fs::read(&out).with_context(|| format!("{program} wrote no {{out}} file"))?
+        } else {
+            output.stdout
+        };
+        wav::decode(&audio).with_context(|| format!("{program} did not produce WAV audio"))?;
+        Ok(audio)
+    }
+
+    fn supports_instruct(&self) -> bool {
+        false
+    }
+}
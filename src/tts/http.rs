@@ -0,0 +1,188 @@
+use crate::audio::wav::{self, Pcm};
+use crate::config::TtsConfig;
+use crate::tts::provider::TtsBackend;
+use anyhow::{bail, Context};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// The sample rate OpenAI documents for `pcm` responses.
+const DEFAULT_PCM_SAMPLE_RATE: u32 = 24_000;
+/// Refuse response bodies larger than this rather than allocating for garbage.
+const MAX_BODY: usize = 64 << 20;
+
+/// Speaks through an OpenAI-compatible `/v1/audio/speech` server, local or
+/// over `https://`.
+pub struct HttpBackend;
+
+impl HttpBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for HttpBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instructions: Option<&'a str>,
+}
+
+impl TtsBackend for HttpBackend {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    fn synthesize(
+        &self,
+        text: &str,
+        config: &TtsConfig,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<u8>> {
+        let http = &config.http;
+        let Some(url) = http.url.as_deref() else {
+            bail!("tts.http.url is not set");
+        };
+        let format = response_format(config);
+        check_format(format)?;
+        let body = serde_json::to_vec(&SpeechRequest {
+            model: http.model.as_deref().unwrap_or("tts-1"),
+            input: text,
+            voice: config
+                .voice
+                .as_deref()
+                .or(http.voice.as_deref())
+                .unwrap_or("alloy"),
+            response_format: format,
+            instructions: config.instruct.as_deref(),
+        })
+        .context("serialize speech request")?;
+
+        // The chain's budget for this attempt, so a slow server leaves the
+        // fallbacks their share instead of getting the worker stopped.
+        let timeout = if timeout.is_zero() {
+            config.timeout_for("http")
+        } else {
+            Some(timeout)
+        };
+        let audio = post(url, &http.headers, &body, timeout)?;
+
+        match format {
+            "wav" => {
+                wav::decode(&audio).context("speech server did not return WAV audio")?;
+                Ok(audio)
+            }
+            "pcm" => {
+                if audio.len() % 2 != 0 {
+                    bail!("speech server returned a partial pcm sample");
+                }
+                wav::encode(&Pcm {
+                    sample_rate: http.pcm_sample_rate.unwrap_or(DEFAULT_PCM_SAMPLE_RATE),
+                    channels: 1,
+                    samples: audio
+                        .chunks_exact(2)
+                        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                        .collect(),
+                })
+            }
+            _ => unreachable!("checked above"),
+        }
+    }
+
+    fn supports_instruct(&self) -> bool {
+        true
+    }
+}
+
+pub fn response_format(config: &TtsConfig) -> &str {
+    config.http.response_format.as_deref().unwrap_or("wav")
+}
+
+/// Only uncompressed audio can be played without a decoder.
+pub fn check_format(format: &str) -> anyhow::Result<()> {
+    match format {
+        "wav" | "pcm" => Ok(()),
+        "mp3" | "opus" | "aac" | "flac" => bail!(
+            "tts.http.response_format {format} is compressed and can't be decoded here; \
+             use wav or pcm"
+        ),
+        other => bail!("unsupported tts.http.response_format {other}; expected wav or pcm"),
+    }
+}
+
+pub fn check_url(url: &str) -> Result<(), String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(format!("expected an http:// or https:// URL: {url}"));
+    }
+    match url.parse::<ureq::http::Uri>() {
+        Ok(uri) if uri.host().is_some_and(|host| !host.is_empty()) => Ok(()),
+        Ok(_) => Err(format!("missing host in {url}")),
+        Err(err) => Err(format!("invalid URL {url}: {err}")),
+    }
+}
+
+/// Header names must be HTTP tokens and values may not break the line, so a
+/// configured header can't smuggle in others.
+pub fn check_header(name: &str, value: &str) -> Result<(), String> {
+    let token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() || !name.chars().all(token) {
+        return Err(format!("invalid header name {name:?}"));
+    }
+    if value.contains(['\r', '\n']) {
+        return Err(format!("header {name} contains a line break"));
+    }
+    Ok(())
+}
+
+fn post(
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    body: &[u8],
+    timeout: Option<Duration>,
+) -> anyhow::Result<Vec<u8>> {
+    check_url(url).map_err(anyhow::Error::msg)?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(timeout)
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut request = agent.post(url).header("Content-Type", "application/json");
+    for (name, value) in headers {
+        check_header(name, value).map_err(anyhow::Error::msg)?;
+        request = request.header(name, value);
+    }
+
+    let mut response = request
+        .send(body)
+        .with_context(|| format!("send speech request to {url}"))?;
+    let status = response.status();
+    let body = match response
+        .body_mut()
+        .with_config()
+        .limit(MAX_BODY as u64)
+        .read_to_vec()
+    {
+        Ok(body) => body,
+        Err(ureq::Error::BodyExceedsLimit(_)) => bail!("response exceeds {MAX_BODY} bytes"),
+        Err(err) => return Err(err).context("read response body"),
+    };
+
+    if !status.is_success() {
+        let detail = String::from_utf8_lossy(&body[..body.len().min(500)]);
+        bail!(
+            "speech server returned {}: {}",
+            status.as_u16(),
+            detail.trim()
+        );
+    }
+    Ok(body)
+}
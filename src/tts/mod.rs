@@ -1,16 +1,22 @@
 pub mod broker;
+pub mod command;
+pub mod http;
 pub mod pocket;
 pub mod provider;
 pub mod qwen3;
+pub mod resident;
+pub mod speechd;
+pub mod worker;
 
-use crate::audio::{cache::AudioCache, renderer};
+use crate::audio::renderer::{self, Playback};
+use crate::audio::{cache::AudioCache, wav};
 use crate::config::Config;
+use crate::events::Priority;
 use anyhow::Context;
 use serde::Serialize;
 use serde_json;
-use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -32,34 +38,106 @@ pub fn synthesize(
     config: &Config,
     backend_override: &Option<String>,
 ) -> anyhow::Result<Vec<u8>> {
-    let backend_name = backend_override
-        .clone()
-        .or_else(|| config.tts.backend.clone())
-        .unwrap_or_else(|| "pocket-tts".to_string());
+    let chain = Chain::new(config, backend_override);
+    let mut failures = Vec::new();
+    for (index, backend_name) in chain.backends.iter().enumerate() {
+        let (cache, cache_key) = cache_entry(text, config, backend_name)?;
+        if let Some(bytes) = cache.get(&cache_key) {
+            return Ok(bytes);
+        }
 
-    let cache_dir = config.default_cache_dir()?;
-    let (max_size_bytes, max_entries) = config.cache_limits();
-    let cache = AudioCache::new(cache_dir, max_size_bytes, max_entries);
-    let config_json = serde_json::to_string(&config.tts).context("serialize tts config")?;
-    let cache_key = AudioCache::key(&backend_name, text, &config_json);
-    if let Some(bytes) = cache.get(&cache_key) {
-        return Ok(bytes);
+        let result = chain
+            .budget(config, index)
+            .and_then(|timeout| synthesize_fresh(text, config, backend_name, timeout));
+        match result {
+            Ok(audio) => {
+                log_producer(index, backend_name);
+                if let Err(err) = cache.put(&cache_key, &audio) {
+                    tracing::debug!(error = ?err, "cache write failed");
+                }
+                return Ok(audio);
+            }
+            Err(err) => {
+                tracing::warn!(backend = %backend_name, error = ?err, "tts backend failed");
+                failures.push((backend_name.clone(), err));
+            }
+        }
     }
+    Err(chain_error(failures))
+}
 
-    let timeout_seconds = config.tts.timeout_seconds;
-    let internal = std::env::var("AGENT_CHIME_INTERNAL_TTS").is_ok();
+/// The primary backend and `tts.fallback_backends`, in order, sharing the
+/// `tts.timeout_seconds` deadline.
+struct Chain {
+    backends: Vec<String>,
+    deadline: Option<Instant>,
+}
 
-    let audio = if timeout_seconds == 0 || internal {
-        synthesize_uncached(text, config, &backend_name)?
-    } else {
-        synthesize_with_timeout(text, config, &backend_name, timeout_seconds)?
-    };
+impl Chain {
+    fn new(config: &Config, backend_override: &Option<String>) -> Self {
+        let mut backends = vec![backend_name(config, backend_override)];
+        for fallback in &config.tts.fallback_backends {
+            if !backends.contains(fallback) {
+                backends.push(fallback.clone());
+            }
+        }
+        let deadline = (config.tts.timeout_seconds > 0)
+            .then(|| Instant::now() + Duration::from_secs(config.tts.timeout_seconds));
+        Self { backends, deadline }
+    }
 
-    if let Err(err) = cache.put(&cache_key, &audio) {
-        tracing::debug!(error = ?err, "cache write failed");
+    /// How long the backend at `index` may take: its `tts.backend_timeouts`
+    /// entry, else an even share of what is left of the deadline between it
+    /// and the backends after it, so a hung primary leaves the fallbacks time
+    /// to run. Always capped by the deadline. Zero means no limit.
+    fn budget(&self, config: &Config, index: usize) -> anyhow::Result<Duration> {
+        let backend_name = &self.backends[index];
+        let own = config
+            .tts
+            .backend_timeouts
+            .get(backend_name)
+            .filter(|seconds| **seconds > 0)
+            .map(|seconds| Duration::from_secs(*seconds));
+        let Some(deadline) = self.deadline else {
+            return Ok(own.unwrap_or(Duration::ZERO));
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            anyhow::bail!(
+                "tts deadline of {}s passed before {backend_name} could run",
+                config.tts.timeout_seconds
+            );
+        }
+        let share = remaining / (self.backends.len() - index) as u32;
+        Ok(own.map_or(share, |own| own.min(remaining)))
     }
+}
 
-    Ok(audio)
+/// A backend that speaks through its own output, so there is no audio to
+/// cache or play.
+fn direct_backend(backend_name: &str) -> Option<Box<dyn provider::TtsBackend>> {
+    provider::select_backend(backend_name)
+        .ok()
+        .filter(|backend| backend.speaks_directly())
+}
+
+fn log_producer(index: usize, backend_name: &str) {
+    if index == 0 {
+        tracing::debug!(backend = %backend_name, "speech synthesized");
+    } else {
+        tracing::info!(backend = %backend_name, "speech synthesized by fallback backend");
+    }
+}
+
+fn chain_error(mut failures: Vec<(String, anyhow::Error)>) -> anyhow::Error {
+    if failures.len() == 1 {
+        return failures.remove(0).1;
+    }
+    let details: Vec<String> = failures
+        .iter()
+        .map(|(backend, err)| format!("{backend}: {err:#}"))
+        .collect();
+    anyhow::anyhow!("all tts backends failed ({})", details.join("; "))
 }
 
 pub fn synthesize_in_process(
@@ -67,97 +145,307 @@ pub fn synthesize_in_process(
     config: &Config,
     backend_override: &Option<String>,
 ) -> anyhow::Result<Vec<u8>> {
-    let backend_name = backend_override
+    synthesize_uncached(
+        text,
+        config,
+        &backend_name(config, backend_override),
+        Duration::ZERO,
+    )
+}
+
+/// Loads the configured backend's model into whatever will serve the next
+/// request (the worker or the resident thread), so the first notification
+/// doesn't pay for it.
+pub fn preload(config: &Config, backend_override: &Option<String>) -> anyhow::Result<()> {
+    // Only the primary is warmed, so it gets the whole deadline.
+    let mut chain = Chain::new(config, backend_override);
+    chain.backends.truncate(1);
+    let backend_name = &chain.backends[0];
+    if direct_backend(backend_name).is_some() {
+        return Ok(());
+    }
+    chain
+        .budget(config, 0)
+        .and_then(|timeout| synthesize_fresh("Ready.", config, backend_name, timeout))
+        .with_context(|| format!("preload {backend_name}"))?;
+    Ok(())
+}
+
+fn cache_entry(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+) -> anyhow::Result<(AudioCache, String)> {
+    let cache_dir = config.default_cache_dir()?;
+    let (max_size_bytes, max_entries) = config.cache_limits();
+    let cache = AudioCache::new(cache_dir, max_size_bytes, max_entries);
+    // The chain settings decide which backend runs, not what it sounds like.
+    let mut tts = config.tts.clone();
+    tts.fallback_backends.clear();
+    tts.backend_timeouts.clear();
+    let config_json = serde_json::to_string(&tts).context("serialize tts config")?;
+    let cache_key = AudioCache::key(backend_name, text, &config_json);
+    Ok((cache, cache_key))
+}
+
+fn backend_name(config: &Config, backend_override: &Option<String>) -> String {
+    backend_override
         .clone()
         .or_else(|| config.tts.backend.clone())
-        .unwrap_or_else(|| "pocket-tts".to_string());
-    synthesize_uncached(text, config, &backend_name)
+        .unwrap_or_else(|| "pocket-tts".to_string())
 }
 
-fn synthesize_uncached(text: &str, config: &Config, backend_name: &str) -> anyhow::Result<Vec<u8>> {
-    let backend = provider::select_backend(backend_name)?;
-    backend
-        .synthesize(text, &config.tts)
-        .with_context(|| format!("synthesize with {backend_name}"))
+/// The end of a time limit that a request's segments share.
+struct Deadline(Option<Instant>);
+
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self((!timeout.is_zero()).then(|| Instant::now() + timeout))
+    }
+
+    /// What is left for the next segment: zero when there is no limit, an
+    /// error once it has passed.
+    fn remaining(&self) -> anyhow::Result<Duration> {
+        let Some(at) = self.0 else {
+            return Ok(Duration::ZERO);
+        };
+        let left = at.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            anyhow::bail!("ran out of time before the next segment");
+        }
+        Ok(left)
+    }
 }
 
-fn synthesize_with_timeout(
+/// Synthesizes `text` with `backend_name`, all segments together within
+/// `timeout` (zero means no limit).
+fn synthesize_uncached(
     text: &str,
     config: &Config,
     backend_name: &str,
-    timeout_seconds: u64,
+    timeout: Duration,
 ) -> anyhow::Result<Vec<u8>> {
-    let exe = std::env::current_exe().context("resolve current executable")?;
-    let mut cmd = Command::new(exe);
-    cmd.arg("__synthesize")
-        .arg("--text")
-        .arg(text)
-        .arg("--backend")
-        .arg(backend_name)
-        .env("AGENT_CHIME_INTERNAL_TTS", "1")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit());
-
-    let mut child = cmd.spawn().context("spawn internal tts worker")?;
-
-    let config_bytes = serde_json::to_vec(config).context("serialize config JSON")?;
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(&config_bytes)
-            .context("write config to tts worker")?;
+    let backend = provider::select_backend(backend_name)?;
+    let deadline = Deadline::after(timeout);
+    let segments = broker::parse_markup(text);
+    if !broker::has_markup(&segments) {
+        let plain = match segments.as_slice() {
+            [broker::Segment::Text(plain)] => plain.as_str(),
+            _ => text,
+        };
+        return backend
+            .synthesize(plain, &config.tts, timeout)
+            .with_context(|| format!("synthesize with {backend_name}"));
     }
 
-    let stdout = child.stdout.take().context("capture tts worker stdout")?;
-    let reader = thread::spawn(move || {
-        let mut buf = Vec::new();
-        let mut handle = stdout;
-        let _ = handle.read_to_end(&mut buf);
-        buf
-    });
-
-    let deadline = Duration::from_secs(timeout_seconds);
-    let start = Instant::now();
-    loop {
-        if let Some(status) = child.try_wait().context("poll tts worker")? {
-            if !status.success() {
-                let _ = reader.join();
-                anyhow::bail!("tts worker exited with status {status}");
+    let mut parts = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match segment {
+            broker::Segment::Text(chunk) => {
+                let audio = backend
+                    .synthesize(&chunk, &config.tts, deadline.remaining()?)
+                    .with_context(|| format!("synthesize segment with {backend_name}"))?;
+                parts.push(wav::Part::Audio(audio));
             }
-            break;
+            broker::Segment::Break(duration) => parts.push(wav::Part::Silence(duration)),
         }
+    }
 
-        if start.elapsed() >= deadline {
-            let _ = child.kill();
-            let _ = child.wait();
-            let _ = reader.join();
-            anyhow::bail!("tts timed out after {timeout_seconds}s");
-        }
+    wav::stitch(&parts).context("stitch synthesized segments")
+}
 
-        thread::sleep(Duration::from_millis(25));
+/// Synthesizes without the cache. With a timeout, this runs on the
+/// background worker, which exits if it overruns and is started again by the
+/// next request.
+fn synthesize_fresh(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+    timeout: Duration,
+) -> anyhow::Result<Vec<u8>> {
+    let internal = std::env::var("AGENT_CHIME_INTERNAL_TTS").is_ok();
+
+    if internal {
+        synthesize_uncached(text, config, backend_name, timeout)
+    } else if !timeout.is_zero() {
+        worker::synthesize(text, config, backend_name, timeout)
+    } else if resident::enabled() {
+        resident::synthesize(text, config, backend_name)
+    } else {
+        synthesize_uncached(text, config, backend_name, timeout)
+    }
+}
+
+/// Like `synthesize_uncached`, but hands audio to `emit` as it is generated.
+fn stream_uncached(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+    timeout: Duration,
+    emit: &mut dyn FnMut(wav::Pcm),
+) -> anyhow::Result<()> {
+    let backend = provider::select_backend(backend_name)?;
+    let deadline = Deadline::after(timeout);
+    // Breaks need the clip's format, so a leading break waits for the first
+    // chunk; the clip then matches what `wav::stitch` makes of it.
+    let mut format = None;
+    let mut leading = Duration::ZERO;
+    for segment in broker::parse_markup(text) {
+        match segment {
+            broker::Segment::Text(chunk) => {
+                for pcm in backend.synthesize_stream(&chunk, &config.tts, deadline.remaining()?) {
+                    let pcm = pcm.with_context(|| format!("synthesize with {backend_name}"))?;
+                    if format.is_none() && !leading.is_zero() {
+                        emit(wav::Pcm::silence(pcm.sample_rate, pcm.channels, leading));
+                    }
+                    format = Some((pcm.sample_rate, pcm.channels));
+                    emit(pcm);
+                }
+            }
+            broker::Segment::Break(duration) => match format {
+                Some((sample_rate, channels)) => {
+                    emit(wav::Pcm::silence(sample_rate, channels, duration))
+                }
+                None => leading += duration,
+            },
+        }
     }
+    Ok(())
+}
 
-    match reader.join() {
-        Ok(audio) => Ok(audio),
-        Err(_) => anyhow::bail!("tts worker panicked while streaming audio"),
+/// Streaming counterpart of `synthesize_fresh`. The resident thread can't
+/// stream, so there the whole clip arrives as one chunk.
+fn stream_fresh(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+    timeout: Duration,
+    emit: &mut dyn FnMut(wav::Pcm),
+) -> anyhow::Result<()> {
+    let internal = std::env::var("AGENT_CHIME_INTERNAL_TTS").is_ok();
+
+    if internal {
+        stream_uncached(text, config, backend_name, timeout, emit)
+    } else if !timeout.is_zero() {
+        worker::stream(text, config, backend_name, timeout, emit)
+    } else if resident::enabled() {
+        let audio = resident::synthesize(text, config, backend_name)?;
+        emit(wav::decode(&audio)?);
+        Ok(())
+    } else {
+        stream_uncached(text, config, backend_name, timeout, emit)
     }
 }
 
-pub fn play_audio(audio: &[u8], volume: f32) -> anyhow::Result<()> {
-    renderer::play_bytes(audio, volume)
+pub fn play_audio(audio: &[u8], playback: &Playback) -> anyhow::Result<()> {
+    renderer::play_bytes(audio, playback)
 }
 
+/// Plays `text` while it is synthesized, then caches the assembled clip.
+/// Cached prompts play straight from the cache; backends that speak
+/// directly bypass both.
 pub fn synthesize_and_play(
     text: &str,
     config: &Config,
     backend_override: &Option<String>,
+    priority: Priority,
 ) -> anyhow::Result<()> {
-    let audio = synthesize(text, config, backend_override)?;
-    play_audio(&audio, config.volume)
+    let playback = Playback::new(config, priority);
+    let (sender, chunks) = mpsc::channel();
+    let (produced, played) = thread::scope(|scope| {
+        let player = scope.spawn(|| renderer::play_stream(chunks, &playback));
+        let produced = stream_chain(text, config, backend_override, priority, &mut |chunk| {
+            let _ = sender.send(chunk);
+        });
+        drop(sender);
+        (produced, player.join())
+    });
+    produced?;
+    played.unwrap_or_else(|_| Err(anyhow::anyhow!("playback thread panicked")))
 }
 
-pub fn models_info() -> anyhow::Result<ModelsInfo> {
-    let config = Config::load()?;
+/// Streams `text` from the first backend in the chain that produces audio,
+/// caching the assembled clip under that backend. A backend is only skipped
+/// if it failed before emitting anything; a clip cut short stays short.
+fn stream_chain(
+    text: &str,
+    config: &Config,
+    backend_override: &Option<String>,
+    priority: Priority,
+    emit: &mut dyn FnMut(wav::Pcm),
+) -> anyhow::Result<()> {
+    let chain = Chain::new(config, backend_override);
+    let mut failures = Vec::new();
+    for (index, backend_name) in chain.backends.iter().enumerate() {
+        if let Some(backend) = direct_backend(backend_name) {
+            let result = chain
+                .budget(config, index)
+                .and_then(|timeout| backend.speak(text, config, priority, timeout));
+            match result {
+                Ok(()) => {
+                    log_producer(index, backend_name);
+                    return Ok(());
+                }
+                Err(err) => {
+                    tracing::warn!(backend = %backend_name, error = ?err, "tts backend failed");
+                    failures.push((backend_name.clone(), err));
+                    continue;
+                }
+            }
+        }
+
+        let (cache, cache_key) = cache_entry(text, config, backend_name)?;
+        if let Some(bytes) = cache.get(&cache_key) {
+            emit(wav::decode(&bytes)?);
+            return Ok(());
+        }
+
+        let mut clip: Option<wav::Pcm> = None;
+        let mut complete = true;
+        let result = chain.budget(config, index).and_then(|timeout| {
+            stream_fresh(text, config, backend_name, timeout, &mut |chunk| {
+                match &mut clip {
+                    Some(clip) => complete &= clip.append(chunk.clone()).is_ok(),
+                    None => clip = Some(chunk.clone()),
+                }
+                emit(chunk);
+            })
+        });
+
+        match (result, clip) {
+            (Ok(()), Some(clip)) => {
+                log_producer(index, backend_name);
+                if complete {
+                    let stored = wav::encode(&clip).and_then(|audio| cache.put(&cache_key, &audio));
+                    if let Err(err) = stored {
+                        tracing::debug!(error = ?err, "cache write failed");
+                    }
+                }
+                return Ok(());
+            }
+            (Err(err), Some(_)) => {
+                tracing::warn!(
+                    backend = %backend_name,
+                    error = ?err,
+                    "synthesis failed partway; clip cut short"
+                );
+                return Ok(());
+            }
+            (Ok(()), None) => {
+                let err = anyhow::anyhow!("{backend_name} produced no audio");
+                tracing::warn!(backend = %backend_name, error = ?err, "tts backend failed");
+                failures.push((backend_name.clone(), err));
+            }
+            (Err(err), None) => {
+                tracing::warn!(backend = %backend_name, error = ?err, "tts backend failed");
+                failures.push((backend_name.clone(), err));
+            }
+        }
+    }
+    Err(chain_error(failures))
+}
+
+pub fn models_info(config: &Config) -> anyhow::Result<ModelsInfo> {
     let cache_dir = config.default_cache_dir().ok();
 
     let backends = vec![
@@ -171,6 +459,21 @@ pub fn models_info() -> anyhow::Result<ModelsInfo> {
             available: cfg!(feature = "qwen3-tts-backend"),
             supports_instruct: true,
         },
+        BackendInfo {
+            name: "command".to_string(),
+            available: !config.tts.command.args.is_empty(),
+            supports_instruct: false,
+        },
+        BackendInfo {
+            name: "http".to_string(),
+            available: config.tts.http.url.is_some(),
+            supports_instruct: true,
+        },
+        BackendInfo {
+            name: "speech-dispatcher".to_string(),
+            available: speechd::is_available(&config.tts),
+            supports_instruct: false,
+        },
     ];
 
     Ok(ModelsInfo {
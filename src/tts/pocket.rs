@@ -7,11 +7,14 @@ mod imp {
     use std::collections::HashMap;
     use std::io::Cursor;
     use std::path::PathBuf;
-    use std::sync::{Arc, Mutex, OnceLock};
+    use std::sync::{mpsc, Arc, Mutex, OnceLock};
+    use std::thread;
+    use std::time::Duration;
 
+    use crate::audio::wav::Pcm;
     use crate::config::TtsConfig;
 
-    use crate::tts::provider::TtsBackend;
+    use crate::tts::provider::{AudioStream, TtsBackend};
 
     static MODEL_CACHE: OnceLock<Mutex<HashMap<String, Arc<TTSModel>>>> = OnceLock::new();
 
@@ -103,6 +106,20 @@ mod imp {
             Self::voice_from_path(model, &path)
         }
 
+        fn prepare(config: &TtsConfig) -> anyhow::Result<(Arc<TTSModel>, ModelState)> {
+            let variant = config.pocket_tts.variant.as_deref().unwrap_or("b6369a24");
+            let use_metal = config.pocket_tts.use_metal.unwrap_or(false);
+            let model = Self::load_model(variant, use_metal, config.allow_downloads)?;
+
+            let voice_spec = config
+                .voice
+                .as_deref()
+                .or(config.pocket_tts.voice.as_deref());
+            let voice_state =
+                Self::resolve_voice_state(&model, voice_spec, config.allow_downloads)?;
+            Ok((model, voice_state))
+        }
+
         fn voice_from_path(model: &TTSModel, path: &PathBuf) -> anyhow::Result<ModelState> {
             let ext = path
                 .extension()
@@ -131,24 +148,56 @@ mod imp {
             "pocket-tts"
         }
 
-        fn synthesize(&self, text: &str, config: &TtsConfig) -> anyhow::Result<Vec<u8>> {
-            let variant = config.pocket_tts.variant.as_deref().unwrap_or("b6369a24");
-            let use_metal = config.pocket_tts.use_metal.unwrap_or(false);
-            let model = Self::load_model(variant, use_metal, config.allow_downloads)?;
-
-            let voice_spec = config
-                .voice
-                .as_deref()
-                .or(config.pocket_tts.voice.as_deref());
-            let voice_state =
-                Self::resolve_voice_state(&model, voice_spec, config.allow_downloads)?;
-
+        fn synthesize(
+            &self,
+            text: &str,
+            config: &TtsConfig,
+            _timeout: Duration,
+        ) -> anyhow::Result<Vec<u8>> {
+            let (model, voice_state) = Self::prepare(config)?;
             let audio = model.generate(text, &voice_state)?;
             let mut cursor = Cursor::new(Vec::new());
             pocket_tts::audio::write_wav_to_writer(&mut cursor, &audio, model.sample_rate as u32)?;
             Ok(cursor.into_inner())
         }
 
+        fn synthesize_stream<'a>(
+            &'a self,
+            text: &str,
+            config: &TtsConfig,
+            _timeout: Duration,
+        ) -> AudioStream<'a> {
+            let (model, voice_state) = match Self::prepare(config) {
+                Ok(prepared) => prepared,
+                Err(err) => return Box::new(std::iter::once(Err(err))),
+            };
+            let text = text.to_string();
+            // The generator borrows the model and voice state, so it runs on
+            // a thread that owns them and hands chunks back as they decode.
+            let (sender, chunks) = mpsc::sync_channel(4);
+            thread::spawn(move || {
+                let sample_rate = model.sample_rate as u32;
+                for chunk in model.generate_stream(&text, &voice_state) {
+                    let pcm = chunk
+                        .and_then(|tensor| Ok(tensor.flatten_all()?.to_vec1::<f32>()?))
+                        .context("generate pocket-tts chunk")
+                        .map(|samples| Pcm {
+                            sample_rate,
+                            channels: 1,
+                            samples: samples
+                                .iter()
+                                .map(|v| (v.clamp(-1.0, 1.0) * 32767.0) as i16)
+                                .collect(),
+                        });
+                    let failed = pcm.is_err();
+                    if sender.send(pcm).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+            Box::new(chunks.into_iter())
+        }
+
         fn supports_instruct(&self) -> bool {
             false
         }
@@ -185,6 +234,7 @@ impl super::provider::TtsBackend for PocketTtsBackend {
         &self,
         _text: &str,
         _config: &crate::config::TtsConfig,
+        _timeout: std::time::Duration,
     ) -> anyhow::Result<Vec<u8>> {
         anyhow::bail!("pocket-tts backend not enabled")
     }
@@ -193,3 +243,34 @@ impl super::provider::TtsBackend for PocketTtsBackend {
         false
     }
 }
+
+/// Checks that a voice spec resolves without downloading anything, mirroring
+/// the offline rules used at synthesis time.
+pub fn check_offline_voice(spec: &str) -> Result<(), String> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err("voice spec is empty".to_string());
+    }
+    if spec.starts_with("hf://") {
+        return Err(format!(
+            "voice '{spec}' requires a download but tts.allow_downloads is false"
+        ));
+    }
+
+    let path = std::path::Path::new(spec);
+    if !path.exists() {
+        return Err(format!(
+            "voice '{spec}' is not a local file and tts.allow_downloads is false"
+        ));
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "safetensors" | "wav" | "wave" => Ok(()),
+        _ => Err(format!("unsupported voice file extension: {ext}")),
+    }
+}
@@ -1,12 +1,65 @@
-use crate::config::TtsConfig;
+use crate::audio::wav::{self, Pcm};
+use crate::config::{Config, TtsConfig};
+use crate::events::Priority;
 use anyhow::bail;
+use std::time::Duration;
 
-use super::{pocket::PocketTtsBackend, qwen3::Qwen3TtsBackend};
+use super::{
+    command::CommandBackend, http::HttpBackend, pocket::PocketTtsBackend, qwen3::Qwen3TtsBackend,
+    speechd::SpeechDispatcherBackend,
+};
+
+/// Audio in the order it was generated; concatenated, the chunks make up
+/// the whole clip.
+pub type AudioStream<'a> = Box<dyn Iterator<Item = anyhow::Result<Pcm>> + Send + 'a>;
 
 pub trait TtsBackend: Send + Sync {
     fn name(&self) -> &str;
-    fn synthesize(&self, text: &str, config: &TtsConfig) -> anyhow::Result<Vec<u8>>;
+
+    /// Returns the audio for `text`. Backends that can stop a request
+    /// themselves give up after `timeout` (zero means the backend's own
+    /// default); the rest rely on the worker being stopped.
+    fn synthesize(
+        &self,
+        text: &str,
+        config: &TtsConfig,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<u8>>;
+
+    /// Yields audio as it is generated. Backends that can't generate
+    /// incrementally yield the finished clip as a single chunk.
+    fn synthesize_stream<'a>(
+        &'a self,
+        text: &str,
+        config: &TtsConfig,
+        timeout: Duration,
+    ) -> AudioStream<'a> {
+        let pcm = self
+            .synthesize(text, config, timeout)
+            .and_then(|audio| wav::decode(&audio));
+        Box::new(std::iter::once(pcm))
+    }
+
     fn supports_instruct(&self) -> bool;
+
+    /// Backends that play speech through their own audio output return true
+    /// and implement `speak`; their speech skips the cache and the renderer.
+    fn speaks_directly(&self) -> bool {
+        false
+    }
+
+    /// Speaks `text`, giving up after `timeout` (zero means the backend's
+    /// own default). It gets the whole config and the event's priority since
+    /// it also stands in for the renderer, volume and queueing included.
+    fn speak(
+        &self,
+        _text: &str,
+        _config: &Config,
+        _priority: Priority,
+        _timeout: Duration,
+    ) -> anyhow::Result<()> {
+        bail!("{} returns audio rather than speaking it", self.name())
+    }
 }
 
 pub fn select_backend(name: &str) -> anyhow::Result<Box<dyn TtsBackend>> {
@@ -31,6 +84,9 @@ pub fn select_backend(name: &str) -> anyhow::Result<Box<dyn TtsBackend>> {
                 bail!("qwen3-tts backend not enabled; rebuild with --features qwen3-tts-backend")
             }
         }
+        "command" => Ok(Box::new(CommandBackend::new())),
+        "http" => Ok(Box::new(HttpBackend::new())),
+        "speech-dispatcher" => Ok(Box::new(SpeechDispatcherBackend::new())),
         _ => bail!("unknown backend: {name}"),
     }
 }
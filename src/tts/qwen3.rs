@@ -8,6 +8,7 @@ mod imp {
     use std::path::Path;
     use std::rc::Rc;
     use std::str::FromStr;
+    use std::time::Duration;
 
     use crate::config::TtsConfig;
 
@@ -136,7 +137,12 @@ mod imp {
             "qwen3-tts"
         }
 
-        fn synthesize(&self, text: &str, config: &TtsConfig) -> anyhow::Result<Vec<u8>> {
+        fn synthesize(
+            &self,
+            text: &str,
+            config: &TtsConfig,
+            _timeout: Duration,
+        ) -> anyhow::Result<Vec<u8>> {
             let model_id = config
                 .qwen3_tts
                 .model
@@ -206,6 +212,7 @@ impl super::provider::TtsBackend for Qwen3TtsBackend {
         &self,
         _text: &str,
         _config: &crate::config::TtsConfig,
+        _timeout: std::time::Duration,
     ) -> anyhow::Result<Vec<u8>> {
         anyhow::bail!("qwen3-tts backend not enabled")
     }
@@ -214,3 +221,35 @@ impl super::provider::TtsBackend for Qwen3TtsBackend {
         true
     }
 }
+
+/// Reports qwen3 language/speaker values the model wouldn't accept.
+#[cfg(feature = "qwen3-tts-backend")]
+pub fn check_voice(config: &crate::config::TtsConfig) -> Vec<(&'static str, String)> {
+    use std::str::FromStr;
+
+    let mut problems = Vec::new();
+    let language = config.qwen3_tts.language.as_deref().unwrap_or("English");
+    if let Err(err) = qwen3_tts::Language::from_str(language) {
+        problems.push(("tts.qwen3_tts.language", format!("{err}")));
+    }
+
+    // The speaker is only used when neither VoiceDesign nor cloning applies.
+    if config.instruct.is_none() && config.qwen3_tts.ref_audio.is_none() {
+        let (path, speaker) = match config.voice.as_deref() {
+            Some(voice) => ("tts.voice", voice),
+            None => (
+                "tts.qwen3_tts.speaker",
+                config.qwen3_tts.speaker.as_deref().unwrap_or("Ryan"),
+            ),
+        };
+        if let Err(err) = qwen3_tts::Speaker::from_str(speaker) {
+            problems.push((path, format!("{err}")));
+        }
+    }
+    problems
+}
+
+#[cfg(not(feature = "qwen3-tts-backend"))]
+pub fn check_voice(_config: &crate::config::TtsConfig) -> Vec<(&'static str, String)> {
+    Vec::new()
+}
@@ -0,0 +1,73 @@
+use super::synthesize_uncached;
+use crate::config::Config;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The thread that keeps models loaded. Backends cache weights per process
+/// (pocket-tts) or per thread (qwen3-tts), so every job runs on it.
+static WORKER: Mutex<Option<Sender<Job>>> = Mutex::new(None);
+
+struct Job {
+    text: String,
+    config: Config,
+    backend: String,
+    reply: Sender<anyhow::Result<Vec<u8>>>,
+}
+
+/// Routes uncached synthesis in this process to a long-lived thread that
+/// keeps models loaded, for `agent-chime daemon`. Only used when
+/// `tts.timeout_seconds` is 0; otherwise the background worker is.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub(super) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub(super) fn synthesize(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let (reply, response) = mpsc::channel();
+    let job = Job {
+        text: text.to_string(),
+        config: config.clone(),
+        backend: backend_name.to_string(),
+        reply,
+    };
+    {
+        let mut worker = WORKER.lock().unwrap_or_else(PoisonError::into_inner);
+        let sender = worker.get_or_insert_with(spawn);
+        if let Err(mpsc::SendError(job)) = sender.send(job) {
+            // The thread died on an earlier job.
+            let sender = spawn();
+            let _ = sender.send(job);
+            *worker = Some(sender);
+        }
+    }
+
+    match response.recv() {
+        Ok(audio) => audio,
+        Err(_) => {
+            *WORKER.lock().unwrap_or_else(PoisonError::into_inner) = None;
+            anyhow::bail!("tts model thread crashed")
+        }
+    }
+}
+
+fn spawn() -> Sender<Job> {
+    let (sender, jobs) = mpsc::channel::<Job>();
+    thread::spawn(move || {
+        for job in jobs {
+            let audio = synthesize_uncached(&job.text, &job.config, &job.backend, Duration::ZERO);
+            let _ = job.reply.send(audio);
+        }
+    });
+    sender
+}
@@ -0,0 +1,209 @@
+use crate::config::{Config, TtsConfig};
+use crate::events::Priority;
+use crate::tts::broker::{parse_markup, Segment};
+use crate::tts::provider::TtsBackend;
+use anyhow::{bail, Context};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long to wait on the daemon when the chain sets no limit.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Hands text to speech-dispatcher over SSIP. The daemon speaks it through
+/// its own output module, so there is no audio to cache or play.
+pub struct SpeechDispatcherBackend;
+
+impl SpeechDispatcherBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SpeechDispatcherBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TtsBackend for SpeechDispatcherBackend {
+    fn name(&self) -> &str {
+        "speech-dispatcher"
+    }
+
+    fn synthesize(
+        &self,
+        _text: &str,
+        _config: &TtsConfig,
+        _timeout: Duration,
+    ) -> anyhow::Result<Vec<u8>> {
+        bail!("speech-dispatcher speaks directly and returns no audio")
+    }
+
+    fn supports_instruct(&self) -> bool {
+        false
+    }
+
+    fn speaks_directly(&self) -> bool {
+        true
+    }
+
+    fn speak(
+        &self,
+        text: &str,
+        config: &Config,
+        priority: Priority,
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        let settings = &config.tts.speech_dispatcher;
+        let timeout = if timeout.is_zero() {
+            DEFAULT_TIMEOUT
+        } else {
+            timeout
+        };
+        let deadline = Instant::now() + timeout;
+        let path = socket_path(&config.tts)?;
+        let stream = UnixStream::connect(&path)
+            .with_context(|| format!("connect to speech-dispatcher at {}", path.display()))?;
+        let mut session = Session {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            timeout,
+            deadline,
+        };
+
+        session.command("SET self CLIENT_NAME user:agent-chime:notify")?;
+        session.command(&format!("SET self PRIORITY {}", ssip_priority(priority)))?;
+        if let Some(voice) = config.tts.voice.as_deref().or(settings.voice.as_deref()) {
+            session.command(&format!("SET self SYNTHESIS_VOICE {voice}"))?;
+        }
+        if let Some(rate) = settings.rate {
+            session.command(&format!("SET self RATE {rate}"))?;
+        }
+        let volume = scaled_volume(settings.volume.unwrap_or(0), config.volume);
+        session.command(&format!("SET self VOLUME {volume}"))?;
+        session.command("SPEAK")?;
+        session.command(&message_body(text))?;
+        // The message is queued; whether the daemon says goodbye is moot.
+        let _ = session.command("QUIT");
+        Ok(())
+    }
+}
+
+/// The SSIP priority for an event: `important` interrupts whatever the
+/// daemon is saying, `message` waits its turn, and `notification` is dropped
+/// if anything else is being said.
+fn ssip_priority(priority: Priority) -> &'static str {
+    match priority {
+        Priority::High => "important",
+        Priority::Normal => "message",
+        Priority::Low => "notification",
+    }
+}
+
+/// Scales an SSIP volume (-100 silent to 100 loudest) by the effective
+/// `volume`, the way the renderer scales a clip, so DND, rule and source
+/// volumes apply to direct speech too.
+fn scaled_volume(volume: i32, scale: f32) -> i32 {
+    let level = (volume.clamp(-100, 100) + 100) as f32 * scale.clamp(0.0, 1.0);
+    level.round() as i32 - 100
+}
+
+/// Whether the speech-dispatcher socket exists.
+pub fn is_available(config: &TtsConfig) -> bool {
+    socket_path(config).is_ok_and(|path| path.exists())
+}
+
+fn socket_path(config: &TtsConfig) -> anyhow::Result<PathBuf> {
+    if let Some(path) = &config.speech_dispatcher.socket {
+        return Ok(path.clone());
+    }
+    if let Ok(address) = std::env::var("SPEECHD_ADDRESS") {
+        match address.strip_prefix("unix_socket:") {
+            Some(path) => return Ok(PathBuf::from(path)),
+            None => bail!("SPEECHD_ADDRESS {address} is not a unix socket"),
+        }
+    }
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+        .context("set tts.speech_dispatcher.socket or XDG_RUNTIME_DIR")?;
+    Ok(PathBuf::from(runtime).join("speech-dispatcher/speechd.sock"))
+}
+
+/// The text of a SPEAK message, markup dropped, ending in the lone `.` that
+/// closes it. Lines starting with `.` are escaped by doubling it.
+fn message_body(text: &str) -> String {
+    let spoken: Vec<String> = parse_markup(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            Segment::Break(_) => None,
+        })
+        .collect();
+    let mut body = String::new();
+    for line in spoken.join(" ").lines() {
+        if line.starts_with('.') {
+            body.push('.');
+        }
+        body.push_str(line);
+        body.push_str("\r\n");
+    }
+    body.push('.');
+    body
+}
+
+struct Session {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    timeout: Duration,
+    deadline: Instant,
+}
+
+impl Session {
+    /// Limits the next socket read or write to what is left of the deadline.
+    fn arm(&self) -> anyhow::Result<()> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            bail!(
+                "speech-dispatcher timed out after {}ms",
+                self.timeout.as_millis()
+            );
+        }
+        // The reader shares this socket, so the timeouts apply to it too.
+        self.writer.set_read_timeout(Some(remaining))?;
+        self.writer.set_write_timeout(Some(remaining))?;
+        Ok(())
+    }
+
+    /// Sends one command (or message body) and checks the reply, which may
+    /// span `NNN-` continuation lines before the final `NNN ` line.
+    fn command(&mut self, command: &str) -> anyhow::Result<()> {
+        self.arm()?;
+        self.writer
+            .write_all(format!("{command}\r\n").as_bytes())
+            .context("write to speech-dispatcher")?;
+        loop {
+            self.arm()?;
+            let mut line = String::new();
+            if self
+                .reader
+                .read_line(&mut line)
+                .context("read from speech-dispatcher")?
+                == 0
+            {
+                bail!("speech-dispatcher closed the connection");
+            }
+            let line = line.trim_end();
+            let Some(code) = line.get(..3).and_then(|code| code.parse::<u16>().ok()) else {
+                bail!("malformed speech-dispatcher reply: {line}");
+            };
+            if line.as_bytes().get(3) == Some(&b'-') {
+                continue;
+            }
+            if !(200..300).contains(&code) {
+                bail!("speech-dispatcher refused: {line}");
+            }
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use super::{stream_uncached, synthesize_uncached};
+use crate::audio::wav::{self, Pcm};
+use crate::config::Config;
+use crate::daemon;
+use crate::runtime;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, TryLockError};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const STATUS_OK: u8 = 0;
+const STATUS_ERR: u8 = 1;
+const STATUS_CHUNK: u8 = 2;
+/// Refuse frames larger than this rather than allocating for garbage.
+const MAX_FRAME: usize = 256 << 20;
+
+const SOCKET_NAME: &str = "worker.sock";
+const LOCK_NAME: &str = "worker.lock";
+const LOG_NAME: &str = "worker.log";
+/// How long a new worker has to start listening.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+/// A worker with no requests for this long exits and frees its model.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+/// How long the worker waits for a connected client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+/// Held back from a request's timeout so a backend that gives up on its own
+/// still has time to send its error before the caller stops waiting.
+const REPLY_MARGIN: Duration = Duration::from_millis(100);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    text: String,
+    backend: String,
+    config: Config,
+    #[serde(default)]
+    stream: bool,
+    /// The caller's time limit in milliseconds; 0 is unlimited.
+    #[serde(default)]
+    timeout_ms: u64,
+}
+
+/// Client for the `__worker` process that keeps the model loaded across
+/// `agent-chime` processes. It listens on `worker.sock` in the runtime dir
+/// and is started detached on first use, so it outlives the `notify` that
+/// started it and serves the next one. It exits on its own when a request
+/// overruns its timeout (the next request starts a fresh one) or after
+/// `IDLE_TIMEOUT` without requests, unless `agent-chime daemon` is running in
+/// the same runtime dir.
+///
+/// Each request is one connection carrying frames: a big-endian `u32`
+/// length, then the payload. The request is JSON; the response is a status
+/// byte followed by WAV bytes or an error message. Streaming requests get a
+/// chunk frame (status 2, WAV bytes) per generated chunk before the final
+/// frame.
+pub struct Worker {
+    program: PathBuf,
+    runtime_dir: PathBuf,
+}
+
+impl Worker {
+    /// A client that starts `program __worker` when no worker is listening
+    /// in `runtime_dir`.
+    pub fn new(program: &Path, runtime_dir: &Path) -> Self {
+        Self {
+            program: program.to_path_buf(),
+            runtime_dir: runtime_dir.to_path_buf(),
+        }
+    }
+
+    pub fn socket_path(&self) -> PathBuf {
+        self.runtime_dir.join(SOCKET_NAME)
+    }
+
+    /// Whether a worker is accepting connections.
+    pub fn is_running(&self) -> bool {
+        UnixStream::connect(self.socket_path()).is_ok()
+    }
+
+    /// Sends one request and waits up to `timeout` (zero waits forever).
+    /// Backend errors come back as responses and leave the worker running.
+    pub fn synthesize(
+        &self,
+        text: &str,
+        config: &Config,
+        backend_name: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<Vec<u8>> {
+        let request = Request {
+            text: text.to_string(),
+            backend: backend_name.to_string(),
+            config: config.clone(),
+            stream: false,
+            timeout_ms: timeout.as_millis() as u64,
+        };
+        self.exchange(&request, timeout, &mut |_| Ok(()))
+    }
+
+    /// Like `synthesize`, but hands each chunk to `emit` as the worker
+    /// generates it. `timeout` covers the whole clip.
+    pub fn stream(
+        &self,
+        text: &str,
+        config: &Config,
+        backend_name: &str,
+        timeout: Duration,
+        emit: &mut dyn FnMut(Pcm),
+    ) -> anyhow::Result<()> {
+        let request = Request {
+            text: text.to_string(),
+            backend: backend_name.to_string(),
+            config: config.clone(),
+            stream: true,
+            timeout_ms: timeout.as_millis() as u64,
+        };
+        self.exchange(&request, timeout, &mut |chunk| {
+            emit(wav::decode(chunk)?);
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Runs one request. A worker that hangs up without answering is
+    /// usually one exiting after another caller's timeout, so the request is
+    /// retried once on a fresh worker.
+    fn exchange(
+        &self,
+        request: &Request,
+        timeout: Duration,
+        on_chunk: &mut dyn FnMut(&[u8]) -> anyhow::Result<()>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let deadline = (!timeout.is_zero()).then(|| Instant::now() + timeout);
+        let request = serde_json::to_vec(request).context("serialize tts request")?;
+        for _ in 0..2 {
+            if let Some(audio) = self.attempt(&request, deadline, timeout, on_chunk)? {
+                return Ok(audio);
+            }
+            tracing::debug!("tts worker hung up without answering");
+        }
+        anyhow::bail!("tts worker exited")
+    }
+
+    /// Returns `None` if the worker hung up before sending any frame.
+    fn attempt(
+        &self,
+        request: &[u8],
+        deadline: Option<Instant>,
+        timeout: Duration,
+        on_chunk: &mut dyn FnMut(&[u8]) -> anyhow::Result<()>,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut stream = self.connect()?;
+        if write_frame(&mut stream, request).is_err() {
+            return Ok(None);
+        }
+
+        let mut answered = false;
+        loop {
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    anyhow::bail!("tts timed out after {timeout:?}");
+                }
+                stream.set_read_timeout(Some(left))?;
+            }
+            let frame = match read_frame(&mut stream) {
+                Ok(Some(frame)) => frame,
+                Ok(None) if !answered => return Ok(None),
+                Ok(None) => anyhow::bail!("tts worker exited"),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    anyhow::bail!("tts timed out after {timeout:?}")
+                }
+                Err(err) => return Err(err).context("read tts worker response"),
+            };
+
+            answered = true;
+            match frame.split_first() {
+                Some((&STATUS_CHUNK, chunk)) => {
+                    on_chunk(chunk).context("malformed tts worker chunk")?;
+                }
+                Some((&STATUS_OK, audio)) => return Ok(Some(audio.to_vec())),
+                Some((&STATUS_ERR, message)) => {
+                    anyhow::bail!("{}", String::from_utf8_lossy(message))
+                }
+                _ => anyhow::bail!("malformed tts worker response"),
+            }
+        }
+    }
+
+    /// Connects to the running worker, starting one if none answers.
+    fn connect(&self) -> anyhow::Result<UnixStream> {
+        runtime::ensure_dir(&self.runtime_dir)?;
+        let path = self.socket_path();
+        if let Ok(stream) = UnixStream::connect(&path) {
+            return Ok(stream);
+        }
+        let exited = self.start()?;
+        let started = Instant::now();
+        loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => return Ok(stream),
+                Err(err) if started.elapsed() >= START_TIMEOUT => {
+                    return Err(err).context("connect to tts worker");
+                }
+                // Nothing is coming; worker.log says why.
+                Err(err) if exited.try_recv().is_ok() => {
+                    return Err(err).context("tts worker exited before listening");
+                }
+                Err(_) => thread::sleep(POLL_INTERVAL),
+            }
+        }
+    }
+
+    /// Starts a worker in its own process group, so it survives the caller
+    /// and the caller's terminal. Its logs go to `worker.log`. The receiver
+    /// hears when it exits.
+    fn start(&self) -> anyhow::Result<mpsc::Receiver<()>> {
+        let log = runtime::open(&self.runtime_dir, LOG_NAME)?;
+        log.set_len(0).context("truncate tts worker log")?;
+        let mut child = Command::new(&self.program)
+            .arg("__worker")
+            .arg("--runtime-dir")
+            .arg(&self.runtime_dir)
+            .env("AGENT_CHIME_INTERNAL_TTS", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log)
+            .process_group(0)
+            .spawn()
+            .context("spawn tts worker")?;
+        tracing::debug!(pid = child.id(), "started tts worker");
+        // Reap it if it exits while this process is still around.
+        let (exited, exit) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = child.wait();
+            let _ = exited.send(());
+        });
+        Ok(exit)
+    }
+}
+
+/// Synthesizes on the worker in `config`'s runtime dir, starting one if
+/// there is none or the last one exited.
+pub(super) fn synthesize(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+    timeout: Duration,
+) -> anyhow::Result<Vec<u8>> {
+    worker(config)?.synthesize(text, config, backend_name, timeout)
+}
+
+pub(super) fn stream(
+    text: &str,
+    config: &Config,
+    backend_name: &str,
+    timeout: Duration,
+    emit: &mut dyn FnMut(Pcm),
+) -> anyhow::Result<()> {
+    worker(config)?.stream(text, config, backend_name, timeout, emit)
+}
+
+fn worker(config: &Config) -> anyhow::Result<Worker> {
+    let exe = std::env::current_exe().context("resolve current executable")?;
+    Ok(Worker::new(&exe, &config.default_runtime_dir()))
+}
+
+/// What the watchdog knows about the request being served.
+struct Watch {
+    /// When the current request started and its time limit.
+    busy: Option<(Instant, Duration)>,
+    last_active: Instant,
+}
+
+/// The `__worker` side: serves requests on the socket in `runtime_dir`, one
+/// at a time, until a request overruns, the worker sits idle with no daemon
+/// running or its socket is removed. Returns if another worker is already listening.
+pub fn serve(runtime_dir: &Path) -> anyhow::Result<()> {
+    let path = runtime_dir.join(SOCKET_NAME);
+    let lock = runtime::open(runtime_dir, LOCK_NAME)?;
+    let started = Instant::now();
+    loop {
+        match lock.try_lock() {
+            Ok(()) => break,
+            // A worker that is exiting still holds the lock for a moment.
+            Err(TryLockError::WouldBlock)
+                if started.elapsed() < START_TIMEOUT && UnixStream::connect(&path).is_err() =>
+            {
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                tracing::debug!("another tts worker is running");
+                return Ok(());
+            }
+            Err(TryLockError::Error(err)) => return Err(err).context("lock tts worker"),
+        }
+    }
+
+    // Holding the lock means any socket here was left by a dead worker.
+    if path.exists() {
+        std::fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(&path).with_context(|| format!("bind {}", path.display()))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .context("restrict socket permissions")?;
+    tracing::info!(socket = %path.display(), "tts worker listening");
+
+    let watch = Arc::new(Mutex::new(Watch {
+        busy: None,
+        last_active: Instant::now(),
+    }));
+    spawn_watchdog(Arc::clone(&watch), runtime_dir.to_path_buf(), lock);
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!(error = ?err, "tts worker accept failed");
+                continue;
+            }
+        };
+        if let Err(err) = answer(&mut stream, &watch) {
+            tracing::debug!(error = ?err, "tts worker request failed");
+        }
+        let mut watch = watch.lock().unwrap_or_else(PoisonError::into_inner);
+        watch.busy = None;
+        watch.last_active = Instant::now();
+    }
+    Ok(())
+}
+
+/// Exits the process when a request overruns its timeout, so a hung model
+/// never holds the socket, when the worker has been idle too long, or when
+/// its socket is gone (the runtime dir was cleaned up). A daemon in the same
+/// runtime dir keeps an idle worker, and its model, around.
+fn spawn_watchdog(watch: Arc<Mutex<Watch>>, runtime_dir: PathBuf, lock: File) {
+    let socket = runtime_dir.join(SOCKET_NAME);
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        if !socket.exists() {
+            tracing::info!("tts worker socket removed; exiting");
+            std::process::exit(0);
+        }
+        let mut watch = watch.lock().unwrap_or_else(PoisonError::into_inner);
+        let reason = match watch.busy {
+            Some((started, timeout)) if !timeout.is_zero() && started.elapsed() > timeout => {
+                "tts request timed out; exiting"
+            }
+            None if watch.last_active.elapsed() > IDLE_TIMEOUT => {
+                if daemon::is_running(&runtime_dir) {
+                    watch.last_active = Instant::now();
+                    continue;
+                }
+                "tts worker idle; exiting"
+            }
+            _ => continue,
+        };
+        tracing::info!("{reason}");
+        let _ = std::fs::remove_file(&socket);
+        drop(lock);
+        std::process::exit(0);
+    });
+}
+
+fn answer(stream: &mut UnixStream, watch: &Mutex<Watch>) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let frame = read_frame(stream)
+        .context("read tts request")?
+        .context("client closed before sending a request")?;
+    let request = serde_json::from_slice::<Request>(&frame).context("parse tts request");
+    if let Ok(request) = &request {
+        let timeout = Duration::from_millis(request.timeout_ms);
+        watch.lock().unwrap_or_else(PoisonError::into_inner).busy = Some((Instant::now(), timeout));
+    }
+
+    let result = request.and_then(|request| {
+        let timeout = Duration::from_millis(request.timeout_ms);
+        let budget = timeout - (timeout / 10).min(REPLY_MARGIN);
+        if !request.stream {
+            return synthesize_uncached(&request.text, &request.config, &request.backend, budget);
+        }
+        let mut written = Ok(());
+        stream_uncached(
+            &request.text,
+            &request.config,
+            &request.backend,
+            budget,
+            &mut |pcm| {
+                if written.is_ok() {
+                    written = wav::encode(&pcm).and_then(|audio| {
+                        let frame = [&[STATUS_CHUNK][..], &audio].concat();
+                        Ok(write_frame(stream, &frame)?)
+                    });
+                }
+            },
+        )?;
+        written.context("write tts chunk")?;
+        Ok(Vec::new())
+    });
+    let response = match result {
+        Ok(audio) => [&[STATUS_OK][..], &audio].concat(),
+        Err(err) => [&[STATUS_ERR][..], format!("{err:#}").as_bytes()].concat(),
+    };
+    write_frame(stream, &response).context("write tts response")
+}
+
+fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Reads one frame, or `None` if the stream ended between frames.
+fn read_frame(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds limit"),
+        ));
+    }
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
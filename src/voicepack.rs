@@ -1,8 +1,8 @@
-use crate::config::{Config, VoicePackRoute};
+use crate::config::{compile_regex, Config, ValidationReport, VoicePackRoute};
 use crate::events::{Event, EventType};
 use anyhow::{Context, Result};
 use rand::seq::SliceRandom;
-use regex::{Regex, RegexBuilder};
+use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
 use voicepack_spec::Manifest;
@@ -23,6 +23,51 @@ pub fn select_audio(event: &Event, config: &Config) -> Result<Option<Vec<u8>>> {
     Ok(pack.select_audio(event))
 }
 
+/// Reports route regexes that don't compile and phrase keys missing from
+/// the manifest.
+pub fn check_routes(config: &Config, report: &mut ValidationReport) {
+    for (index, route) in config.voicepack.routes.iter().enumerate() {
+        if let Err(message) = compile_regex(&route.pattern, route.case_sensitive) {
+            report.push(format!("voicepack.routes[{index}].pattern"), message);
+        }
+    }
+
+    let Some(manifest_path) = config.voicepack_manifest_path() else {
+        return;
+    };
+    if !manifest_path.exists() {
+        return;
+    }
+    let manifest = match load_manifest(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            report.push("voicepack.manifest_path", format!("{err:#}"));
+            return;
+        }
+    };
+
+    for (index, route) in config.voicepack.routes.iter().enumerate() {
+        for (phrase_index, key) in route.phrases.iter().enumerate() {
+            if !manifest.phrases.contains_key(key) {
+                report.push(
+                    format!("voicepack.routes[{index}].phrases[{phrase_index}]"),
+                    format!(
+                        "phrase '{key}' not found in manifest {}",
+                        manifest_path.display()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn load_manifest(manifest_path: &Path) -> Result<Manifest> {
+    let raw = fs::read_to_string(manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    serde_json::from_str(&raw)
+        .with_context(|| format!("parse manifest {}", manifest_path.display()))
+}
+
 struct VoicePack {
     root: PathBuf,
     manifest: Manifest,
@@ -37,10 +82,7 @@ struct RouteRule {
 
 impl VoicePack {
     fn load(manifest_path: &Path, routes: &[VoicePackRoute]) -> Result<Self> {
-        let raw = fs::read_to_string(manifest_path)
-            .with_context(|| format!("read manifest {}", manifest_path.display()))?;
-        let manifest: Manifest = serde_json::from_str(&raw)
-            .with_context(|| format!("parse manifest {}", manifest_path.display()))?;
+        let manifest = load_manifest(manifest_path)?;
         let root = manifest_path
             .parent()
             .map(|p| p.to_path_buf())
@@ -51,10 +93,8 @@ impl VoicePack {
             if route.phrases.is_empty() {
                 continue;
             }
-            let mut builder = RegexBuilder::new(&route.pattern);
-            builder.case_insensitive(!route.case_sensitive);
-            let regex = builder
-                .build()
+            let regex = compile_regex(&route.pattern, route.case_sensitive)
+                .map_err(anyhow::Error::msg)
                 .with_context(|| format!("compile voicepack route regex: {}", route.pattern))?;
             compiled_routes.push(RouteRule {
                 events: route.events.clone(),
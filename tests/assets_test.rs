@@ -0,0 +1,90 @@
+use agent_chime::assets;
+use agent_chime::audio::wav;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Runs the CLI in `cwd` with `data_dir` set and the user's own config and
+/// data out of reach.
+fn run(cwd: &Path, data_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_agent-chime"))
+        .current_dir(cwd)
+        .env("HOME", cwd)
+        .env("XDG_CONFIG_HOME", cwd.join("config"))
+        .env("XDG_DATA_HOME", cwd.join("data"))
+        .arg("--set")
+        .arg(format!("data_dir={}", data_dir.display()))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn install(cwd: &Path, data_dir: &Path, force: bool) -> String {
+    let mut args = vec!["assets", "install"];
+    if force {
+        args.push("--force");
+    }
+    let output = run(cwd, data_dir, &args);
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn install_writes_every_embedded_file_to_the_data_dir() {
+    let home = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+
+    let stdout = install(home.path(), data.path(), false);
+    assert!(
+        stdout.contains(&format!("Installed {} files", assets::FILES.len())),
+        "{stdout}"
+    );
+    for (name, bytes) in assets::FILES {
+        assert_eq!(
+            std::fs::read(data.path().join(name)).unwrap(),
+            *bytes,
+            "{name}"
+        );
+        if name.ends_with(".wav") {
+            wav::decode(bytes).unwrap();
+        }
+    }
+}
+
+#[test]
+fn reinstall_keeps_edited_files_unless_forced() {
+    let home = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+    install(home.path(), data.path(), false);
+
+    let yield_wav = data.path().join("earcons/yield.wav");
+    std::fs::write(&yield_wav, b"edited").unwrap();
+    assert!(install(home.path(), data.path(), false).contains("Installed 0 files"));
+    assert_eq!(std::fs::read(&yield_wav).unwrap(), b"edited");
+    install(home.path(), data.path(), true);
+    assert_ne!(std::fs::read(&yield_wav).unwrap(), b"edited");
+}
+
+#[test]
+fn installed_voicepack_stands_in_for_a_checkout() {
+    let home = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+    install(home.path(), data.path(), false);
+
+    let manifest_problem = |data_dir: &Path| {
+        let output = run(
+            home.path(),
+            data_dir,
+            &[
+                "--set",
+                "voicepack.enabled=true",
+                "config",
+                "--validate",
+                "--json",
+            ],
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout.contains(r#""path": "voicepack.manifest_path""#)
+    };
+    assert!(!manifest_problem(data.path()));
+    assert!(manifest_problem(&home.path().join("empty")));
+}
@@ -0,0 +1,56 @@
+use agent_chime::events::{Event, EventType, Source};
+use agent_chime::tts::broker::{check_template, parse_markup, render_template, Segment};
+use std::time::Duration;
+
+#[test]
+fn plain_text_is_single_segment() {
+    let segments = parse_markup("Build done.");
+    assert_eq!(segments, vec![Segment::Text("Build done.".to_string())]);
+}
+
+#[test]
+fn break_tags_split_segments() {
+    let segments = parse_markup("Build done.<break 300ms/> Tests passed.<break time=\"1.5s\"/>");
+    assert_eq!(
+        segments,
+        vec![
+            Segment::Text("Build done.".to_string()),
+            Segment::Break(Duration::from_millis(300)),
+            Segment::Text("Tests passed.".to_string()),
+            Segment::Break(Duration::from_millis(1500)),
+        ]
+    );
+}
+
+#[test]
+fn unsupported_tags_are_dropped() {
+    let segments = parse_markup("This is <emphasis>really</emphasis> done.");
+    assert_eq!(
+        segments,
+        vec![Segment::Text("This is really done.".to_string())]
+    );
+}
+
+#[test]
+fn template_check_flags_unknown_placeholders_and_empty_output() {
+    assert!(check_template("Build done.<break 300ms/> {summary}").is_ok());
+    assert!(check_template("Hi {nmae}").is_err());
+    assert!(check_template("<break/>").is_err());
+}
+
+#[test]
+fn summary_is_spoken_literally() {
+    let event = Event::with_summary(
+        EventType::AgentYield,
+        Source::Claude,
+        Some("Returns Vec<String> & {source}.<break 9s/>".to_string()),
+    );
+    let rendered = render_template("{source}: {summary}<break 300ms/>", &event);
+    assert_eq!(
+        parse_markup(&rendered),
+        vec![
+            Segment::Text("claude: Returns Vec<String> & {source}.<break 9s/>".to_string()),
+            Segment::Break(Duration::from_millis(300)),
+        ]
+    );
+}
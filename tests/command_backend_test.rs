@@ -0,0 +1,157 @@
+use agent_chime::audio::wav::{self, Pcm};
+use agent_chime::config::Config;
+use agent_chime::tts::{self, provider::select_backend};
+use std::path::Path;
+use std::time::Duration;
+
+fn clip(dir: &Path) -> (Vec<u8>, String) {
+    let audio = wav::encode(&Pcm {
+        sample_rate: 22_050,
+        channels: 1,
+        samples: vec![0, 100, -100, 0],
+    })
+    .unwrap();
+    let path = dir.join("clip.wav");
+    std::fs::write(&path, &audio).unwrap();
+    (audio, path.display().to_string())
+}
+
+fn command_config(args: &[&str]) -> Config {
+    let mut config = Config::default();
+    config.tts.backend = Some("command".to_string());
+    config.tts.command.args = args.iter().map(|arg| arg.to_string()).collect();
+    config
+}
+
+#[test]
+fn command_reads_stdin_and_writes_stdout() {
+    let dir = tempfile::tempdir().unwrap();
+    let (audio, wav_path) = clip(dir.path());
+    let heard = dir.path().join("heard.txt").display().to_string();
+    let script = format!("cat > '{heard}'; cat '{wav_path}'");
+    let config = command_config(&["sh", "-c", &script]);
+
+    let backend = select_backend("command").unwrap();
+    assert_eq!(
+        backend
+            .synthesize("Build done.", &config.tts, Duration::ZERO)
+            .unwrap(),
+        audio
+    );
+    assert_eq!(std::fs::read_to_string(&heard).unwrap(), "Build done.");
+}
+
+#[test]
+fn command_takes_text_argument_and_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (audio, wav_path) = clip(dir.path());
+    let heard = dir.path().join("heard.txt").display().to_string();
+    let script = format!("printf %s \"$1\" > '{heard}'; cp '{wav_path}' \"$2\"");
+    let config = command_config(&["sh", "-c", &script, "sh", "{text}", "{out}"]);
+
+    let backend = select_backend("command").unwrap();
+    assert_eq!(
+        backend
+            .synthesize("it's done", &config.tts, Duration::ZERO)
+            .unwrap(),
+        audio
+    );
+    assert_eq!(std::fs::read_to_string(&heard).unwrap(), "it's done");
+}
+
+#[test]
+fn command_failures_are_reported() {
+    let backend = select_backend("command").unwrap();
+
+    let config = command_config(&["sh", "-c", "echo no voice model >&2; exit 2"]);
+    let err = backend
+        .synthesize("hi", &config.tts, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("no voice model"), "{err:#}");
+
+    let config = command_config(&["sh", "-c", "echo not audio"]);
+    let err = backend
+        .synthesize("hi", &config.tts, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("did not produce WAV"), "{err:#}");
+
+    let err = backend
+        .synthesize("hi", &Config::default().tts, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("tts.command.args"), "{err:#}");
+}
+
+#[test]
+fn command_output_is_cached() {
+    let dir = tempfile::tempdir().unwrap();
+    let (audio, wav_path) = clip(dir.path());
+    let runs = dir.path().join("runs").display().to_string();
+    let script = format!("echo run >> '{runs}'; cp '{wav_path}' \"$1\"");
+    let mut config = command_config(&["sh", "-c", &script, "sh", "{out}"]);
+    config.cache_dir = Some(dir.path().join("cache"));
+    config.tts.timeout_seconds = 0;
+
+    for _ in 0..2 {
+        assert_eq!(tts::synthesize("Cached.", &config, &None).unwrap(), audio);
+    }
+    assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\n");
+}
+
+#[test]
+fn command_backend_needs_args() {
+    let report = command_config(&[]).validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"tts.command.args"), "{paths:?}");
+}
+
+#[test]
+fn streamed_clip_keeps_a_leading_break() {
+    let dir = tempfile::tempdir().unwrap();
+    let (audio, wav_path) = clip(dir.path());
+    // Stands in for `afplay`, so playback succeeds anywhere.
+    let bin = dir.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let player = bin.join("afplay");
+    std::fs::write(&player, "#!/bin/sh\nexit 0\n").unwrap();
+    let mut permissions = std::fs::metadata(&player).unwrap().permissions();
+    std::os::unix::fs::PermissionsExt::set_mode(&mut permissions, 0o755);
+    std::fs::set_permissions(&player, permissions).unwrap();
+
+    // Without `--output`, test-tts streams and caches what it played.
+    let cache_dir = dir.path().join("cache");
+    let runtime_dir = dir.path().join("runtime");
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_agent-chime"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("PATH", path)
+        .args(["--set", &format!("runtime_dir={}", runtime_dir.display())])
+        .args(["--set", &format!("cache_dir={}", cache_dir.display())])
+        .args([
+            "--set",
+            &format!(r#"tts.command.args=["cat", "{wav_path}"]"#),
+        ])
+        .args([
+            "test-tts",
+            "--backend",
+            "command",
+            "--text",
+            "<break 1s/>Hi",
+        ])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(runtime_dir.join("worker.sock"));
+    assert!(output.status.success(), "{output:?}");
+
+    let cached: Vec<_> = std::fs::read_dir(&cache_dir)
+        .unwrap()
+        .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    let stitched = wav::stitch(&[
+        wav::Part::Silence(std::time::Duration::from_secs(1)),
+        wav::Part::Audio(audio),
+    ])
+    .unwrap();
+    assert_eq!(cached, [stitched]);
+}
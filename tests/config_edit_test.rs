@@ -0,0 +1,158 @@
+use agent_chime::config::{Config, Layer, LoadOptions};
+use serde_json::json;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// A repo holding `project` as its config, and options that resolve against
+/// it alone. Returns the project file's path too.
+fn fixture(project: &str) -> (TempDir, LoadOptions, PathBuf) {
+    fixture_named("agent-chime.json", project)
+}
+
+fn fixture_named(name: &str, project: &str) -> (TempDir, LoadOptions, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    let path = repo.join(name);
+    std::fs::write(&path, project).unwrap();
+    let options = LoadOptions {
+        system_dir: Some(dir.path().join("system")),
+        user_dir: Some(dir.path().join("user")),
+        state_dir: Some(dir.path().join("state")),
+        cwd: Some(repo),
+        env: Some(Vec::new()),
+        ..Default::default()
+    };
+    (dir, options, path)
+}
+
+fn document(path: &PathBuf) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn set_edits_the_project_file_and_keeps_unknown_keys() {
+    let (_dir, options, path) =
+        fixture(r#"{"schema_version": 1, "volume": 0.6, "custom": {"keep": true}}"#);
+
+    let written = Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap();
+    assert_eq!(written, path);
+    Config::set_key(&options, Layer::Project, "tts.voice", "123").unwrap();
+    Config::set_key(
+        &options,
+        Layer::Project,
+        "events.agent_yield.enabled",
+        "false",
+    )
+    .unwrap();
+
+    assert_eq!(
+        document(&path),
+        json!({
+            "schema_version": 1,
+            "volume": 0.4,
+            "custom": {"keep": true},
+            "tts": {"voice": "123"},
+            "events": {"AGENT_YIELD": {"enabled": false}}
+        })
+    );
+    assert_eq!(
+        Config::get_key(&options, "tts.voice").unwrap(),
+        json!("123")
+    );
+    assert_eq!(
+        Config::get_key(&options, "events.AGENT_YIELD.enabled").unwrap(),
+        json!(false)
+    );
+}
+
+#[test]
+fn set_rejects_values_that_fail_validation() {
+    let (_dir, options, path) = fixture(r#"{"schema_version": 1, "volume": 0.6}"#);
+    assert!(Config::set_key(&options, Layer::Project, "volume", "loud").is_err());
+    assert!(Config::set_key(&options, Layer::Project, "volume", "7").is_err());
+    assert_eq!(document(&path)["volume"], 0.6);
+}
+
+#[test]
+fn unset_removes_the_key_and_empty_tables() {
+    let (_dir, options, path) = fixture(r#"{"schema_version": 1, "tts": {"voice": "123"}}"#);
+
+    Config::unset_key(&options, Layer::Project, "tts.voice").unwrap();
+    assert!(Config::unset_key(&options, Layer::Project, "tts.voice").is_err());
+    assert!(document(&path).get("tts").is_none());
+    assert_eq!(Config::get_key(&options, "tts.voice").unwrap(), json!(null));
+}
+
+#[test]
+fn unmigrated_file_is_left_alone_until_migrated() {
+    let raw = r#"{"volume": 0.6}"#;
+    let (_dir, options, path) = fixture(raw);
+
+    let err = Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap_err();
+    assert!(err.to_string().contains("config migrate"), "{err:#}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), raw);
+
+    Config::migrate_file(&path).unwrap().unwrap();
+    Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap();
+    assert_eq!(document(&path)["volume"], 0.4);
+}
+
+#[test]
+fn json_edits_keep_the_key_order() {
+    let (_dir, options, path) =
+        fixture(r#"{"volume": 0.6, "schema_version": 1, "cache_max_mb": 50}"#);
+
+    Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    let keys: Vec<usize> = ["volume", "schema_version", "cache_max_mb"]
+        .iter()
+        .map(|key| written.find(key).unwrap())
+        .collect();
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "{written}");
+}
+
+#[test]
+fn toml_edits_touch_only_the_changed_key() {
+    let original = "\
+# Shared settings for this repo.
+schema_version = 1
+volume = 0.6 # quiet, for the open-plan office
+
+[tts]
+# Ask before changing the voice.
+voice = \"alba\"
+";
+    let (_dir, options, path) = fixture_named("agent-chime.toml", original);
+
+    Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap();
+    Config::set_key(&options, Layer::Project, "tts.instruct", "calm").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        original.replace("volume = 0.6", "volume = 0.4").replace(
+            "voice = \"alba\"\n",
+            "voice = \"alba\"\ninstruct = \"calm\"\n"
+        )
+    );
+
+    Config::unset_key(&options, Layer::Project, "tts.instruct").unwrap();
+    Config::set_key(&options, Layer::Project, "volume", "0.6").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+}
+
+#[test]
+fn yaml_with_comments_is_not_rewritten() {
+    let original = "# Shared settings.\nschema_version: 1\nvolume: 0.6\n";
+    let (_dir, options, path) = fixture_named("agent-chime.yaml", original);
+
+    let err = Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap_err();
+    assert!(err.to_string().contains("edit it by hand"), "{err:#}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+    std::fs::write(&path, "schema_version: 1\nvolume: 0.6\n").unwrap();
+    Config::set_key(&options, Layer::Project, "volume", "0.4").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "schema_version: 1\nvolume: 0.4\n"
+    );
+}
@@ -0,0 +1,399 @@
+use agent_chime::config::{
+    find_project_config, Config, ConfigFormat, DiscoveryConfig, Layer, LoadOptions, Mode,
+    SCHEMA_VERSION,
+};
+use agent_chime::events::{EventType, Source};
+use std::path::{Path, PathBuf};
+
+/// Options that see only `dir`: no system or user config, no saved profile
+/// and no `AGENT_CHIME_*` environment. Returns them with the cwd they use.
+fn isolated(dir: &Path) -> (LoadOptions, PathBuf) {
+    let cwd = dir.join("repo");
+    std::fs::create_dir_all(cwd.join(".git")).unwrap();
+    let options = LoadOptions {
+        system_dir: Some(dir.join("system")),
+        user_dir: Some(dir.join("user")),
+        state_dir: Some(dir.join("state")),
+        cwd: Some(cwd.clone()),
+        env: Some(Vec::new()),
+        ..Default::default()
+    };
+    (options, cwd)
+}
+
+#[test]
+fn source_overrides_fall_through_to_global() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent-chime.json");
+    std::fs::write(
+        &path,
+        r#"{
+            "volume": 0.5,
+            "sources": {
+                "codex": {
+                    "volume": 0.3,
+                    "voice": "azelma",
+                    "events": { "AGENT_YIELD": { "mode": "earcon" } }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let config = Config::load_from_path(&path).unwrap();
+
+    let codex = config.for_source(Source::Codex);
+    assert_eq!(codex.volume, 0.3);
+    assert_eq!(codex.tts.voice.as_deref(), Some("azelma"));
+    assert_eq!(codex.events[&EventType::AgentYield].mode, Mode::Earcon);
+    assert_eq!(
+        codex.events[&EventType::AgentYield].template.as_deref(),
+        Some("Ready.")
+    );
+
+    let claude = config.for_source(Source::Claude);
+    assert_eq!(claude.volume, 0.5);
+    assert_eq!(claude.tts.voice, None);
+    assert_eq!(claude.events[&EventType::AgentYield].mode, Mode::Tts);
+}
+
+#[test]
+fn project_overrides_match_path_globs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent-chime.json");
+    let projects = serde_json::json!({
+        "projects": [
+            { "path": format!("{}/work/*", dir.path().display()), "volume": 0.2 },
+            { "path": "/nowhere/**", "voice": "azelma" }
+        ]
+    });
+    std::fs::write(&path, projects.to_string()).unwrap();
+    let config = Config::load_from_path(&path).unwrap();
+
+    let nested = dir.path().join("work").join("monorepo").join("crates");
+    let matched = config.for_project(&nested);
+    assert_eq!(matched.volume, 0.2);
+    assert_eq!(matched.tts.voice, None);
+
+    let unmatched = config.for_project(dir.path());
+    assert_eq!(unmatched.volume, config.volume);
+}
+
+#[test]
+fn project_config_is_found_up_to_git_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    let nested = repo.join("crates").join("core");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(repo.join(".git")).unwrap();
+    std::fs::write(repo.join(".agent-chime.json"), "{}").unwrap();
+    std::fs::write(dir.path().join("agent-chime.json"), "{}").unwrap();
+
+    let settings = DiscoveryConfig::default();
+    let found = find_project_config(&nested, &settings);
+    assert_eq!(found.found, Some(repo.join(".agent-chime.json")));
+
+    std::fs::remove_file(repo.join(".agent-chime.json")).unwrap();
+    let missing = find_project_config(&nested, &settings);
+    assert_eq!(missing.found, None);
+    assert_eq!(missing.boundary, repo);
+}
+
+#[test]
+fn discovery_outside_a_repo_searches_to_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("a/b");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(dir.path().join("agent-chime.json"), "{}").unwrap();
+
+    let discovery = find_project_config(&nested, &DiscoveryConfig::default());
+    assert_eq!(discovery.found, Some(dir.path().join("agent-chime.json")));
+    assert_eq!(discovery.boundary, std::path::Path::new("/"));
+    let described = discovery.describe();
+    assert!(
+        described.contains("filesystem root (not in a git repo)"),
+        "{described}"
+    );
+}
+
+#[test]
+fn cli_overrides_win_and_record_provenance() {
+    let dir = tempfile::tempdir().unwrap();
+    let (isolated, _) = isolated(dir.path());
+    let options = LoadOptions {
+        overrides: vec![
+            "volume=0.25".to_string(),
+            "events.agent_yield.mode=silent".to_string(),
+        ],
+        ..isolated
+    };
+    let (config, resolution) = Config::resolve(&options).unwrap();
+    assert_eq!(config.volume, 0.25);
+    assert_eq!(config.events[&EventType::AgentYield].mode, Mode::Silent);
+    assert_eq!(resolution.layer_for("volume"), Layer::Cli);
+    assert_eq!(resolution.layer_for("events.AGENT_YIELD.mode"), Layer::Cli);
+    // Kept apart so they can outrank per-event overrides at delivery.
+    assert_eq!(resolution.pinned["volume"], 0.25);
+    assert_eq!(resolution.pinned["events"]["AGENT_YIELD"]["mode"], "silent");
+    assert!(resolution.pinned.get("tts").is_none());
+}
+
+#[test]
+fn layers_stack_in_order_and_env_beats_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut options, cwd) = isolated(dir.path());
+    std::fs::create_dir_all(dir.path().join("system")).unwrap();
+    std::fs::create_dir_all(dir.path().join("user")).unwrap();
+    std::fs::write(
+        dir.path().join("system/config.json"),
+        r#"{"volume": 0.1, "cache_max_mb": 10, "tts": {"voice": "alba"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("user/config.toml"),
+        "volume = 0.2\ncache_max_mb = 20\n",
+    )
+    .unwrap();
+    std::fs::write(cwd.join("agent-chime.json"), r#"{"volume": 0.3}"#).unwrap();
+    options.env = Some(vec![(
+        "AGENT_CHIME_TTS__VOICE".to_string(),
+        "azelma".to_string(),
+    )]);
+
+    let (config, resolution) = Config::resolve(&options).unwrap();
+    assert_eq!(config.volume, 0.3);
+    assert_eq!(config.cache_max_mb, Some(20));
+    assert_eq!(config.tts.voice.as_deref(), Some("azelma"));
+    assert_eq!(resolution.layer_for("volume"), Layer::Project);
+    assert_eq!(resolution.layer_for("cache_max_mb"), Layer::User);
+    assert_eq!(resolution.layer_for("tts.voice"), Layer::Env);
+    assert_eq!(resolution.pinned["tts"]["voice"], "azelma");
+}
+
+#[test]
+fn toml_and_yaml_round_trip_like_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let expected = serde_json::to_value(Config::default()).unwrap();
+    for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+        let path = dir
+            .path()
+            .join(format!("agent-chime.{}", format.extension()));
+        std::fs::write(&path, format.render(&Config::default()).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&path).unwrap();
+        assert_eq!(
+            serde_json::to_value(loaded).unwrap(),
+            expected,
+            "{format:?}"
+        );
+    }
+
+    let path = dir.path().join("partial.toml");
+    std::fs::write(
+        &path,
+        "volume = 0.5\n\n[events.AGENT_YIELD]\nmode = \"earcon\"\n",
+    )
+    .unwrap();
+    let partial = Config::load_from_path(&path).unwrap();
+    assert_eq!(partial.volume, 0.5);
+    assert_eq!(partial.events[&EventType::AgentYield].mode, Mode::Earcon);
+    assert_eq!(
+        partial.events[&EventType::AgentYield].template.as_deref(),
+        Some("Ready.")
+    );
+}
+
+#[test]
+fn check_reports_every_problem_with_its_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let (isolated, _) = isolated(dir.path());
+    let options = LoadOptions {
+        overrides: vec![
+            "events.agent_yield.mode=tss".to_string(),
+            "volume=3".to_string(),
+            "cache_max_entries=0".to_string(),
+            r#"tts.fallback_backends=["pocket-tts", "espeak"]"#.to_string(),
+        ],
+        ..isolated
+    };
+    let report = Config::check(&options);
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"events.AGENT_YIELD.mode"), "{paths:?}");
+    assert!(paths.contains(&"volume"), "{paths:?}");
+    assert!(paths.contains(&"cache_max_entries"), "{paths:?}");
+    assert!(paths.contains(&"tts.fallback_backends[1]"), "{paths:?}");
+    let volume = report.issues.iter().find(|i| i.path == "volume").unwrap();
+    assert_eq!(volume.origin.as_deref(), Some("cli"));
+}
+
+#[test]
+fn unversioned_config_is_upgraded_on_load_and_by_migrate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let legacy = r#"{
+        "volume": 0.5,
+        "tts": { "voice": "azelma" },
+        "custom": 1
+    }"#;
+    std::fs::write(&path, legacy).unwrap();
+
+    let config = Config::load_from_path(&path).unwrap();
+    assert_eq!(config.schema_version, SCHEMA_VERSION);
+    assert_eq!(config.tts.voice.as_deref(), Some("azelma"));
+    // Loading never touches the file.
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), legacy);
+
+    let migration = Config::migrate_file(&path).unwrap().unwrap();
+    assert_eq!(migration.from, 0);
+    let backup = migration.backup.unwrap();
+    assert_eq!(std::fs::read_to_string(&backup).unwrap(), legacy);
+    let migrated: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(migrated["schema_version"], SCHEMA_VERSION);
+    assert_eq!(migrated["tts"]["voice"], "azelma");
+    assert_eq!(migrated["custom"], 1);
+    assert!(Config::migrate_file(&path).unwrap().is_none());
+
+    std::fs::write(&path, r#"{"schema_version": 99}"#).unwrap();
+    assert!(Config::load_from_path(&path).is_err());
+}
+
+#[test]
+fn python_layout_is_migrated_on_load_and_by_migrate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let legacy = r#"{
+        "backend": "pocket-tts",
+        "voice": "azelma",
+        "timeout": 2.5,
+        "volume": 0.5,
+        "cache": { "max_mb": 20 },
+        "earcons": "/sounds",
+        "tts": { "pocket": { "voice": "marius" } },
+        "voicepack": "/packs/default/manifest.json",
+        "events": { "agent-yield": { "mode": "EARCON" } },
+        "sources": { "codex": { "events": { "error_retry": { "mode": "TTS" } } } },
+        "custom": 1
+    }"#;
+    std::fs::write(&path, legacy).unwrap();
+
+    let config = Config::load_from_path(&path).unwrap();
+    assert_eq!(config.tts.backend.as_deref(), Some("pocket-tts"));
+    assert_eq!(config.tts.voice.as_deref(), Some("azelma"));
+    assert_eq!(config.tts.timeout_seconds, 3);
+    assert_eq!(config.tts.pocket_tts.voice.as_deref(), Some("marius"));
+    assert_eq!(config.cache_max_mb, Some(20));
+    assert_eq!(config.earcons_dir, Some(PathBuf::from("/sounds")));
+    assert!(config.voicepack.enabled);
+    assert_eq!(
+        config.voicepack.manifest_path,
+        Some(PathBuf::from("/packs/default/manifest.json"))
+    );
+    assert_eq!(config.events[&EventType::AgentYield].mode, Mode::Earcon);
+    let codex = config.for_source(Source::Codex);
+    assert_eq!(codex.events[&EventType::ErrorRetry].mode, Mode::Tts);
+
+    // The migrated file loads to the same config the legacy one did.
+    Config::migrate_file(&path).unwrap().unwrap();
+    let migrated: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(migrated["tts"]["pocket_tts"]["voice"], "marius");
+    assert_eq!(migrated["custom"], 1);
+    for key in ["backend", "voice", "timeout", "cache", "earcons"] {
+        assert!(migrated.get(key).is_none(), "{key} left behind");
+    }
+    let reloaded = Config::load_from_path(&path).unwrap();
+    assert_eq!(
+        serde_json::to_value(&reloaded).unwrap(),
+        serde_json::to_value(&config).unwrap()
+    );
+}
+
+#[test]
+fn legacy_keys_yield_to_current_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{"voice": "azelma", "tts": {"voice": "alba"}}"#).unwrap();
+
+    let config = Config::load_from_path(&path).unwrap();
+    assert_eq!(config.tts.voice.as_deref(), Some("alba"));
+}
+
+#[test]
+fn env_and_cli_values_outrank_source_and_project_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent-chime.json");
+    std::fs::write(
+        &path,
+        r#"{
+            "sources": { "codex": { "volume": 0.3, "voice": "azelma" } },
+            "projects": [{ "path": "/work/**", "volume": 0.9 }]
+        }"#,
+    )
+    .unwrap();
+    let config = Config::load_from_path(&path).unwrap();
+    let pinned = serde_json::json!({ "volume": 0.25 });
+
+    let delivered = config
+        .for_source(Source::Codex)
+        .for_project(std::path::Path::new("/work/acme"))
+        .pin(&pinned)
+        .unwrap();
+    assert_eq!(delivered.volume, 0.25);
+    // Keys the env and CLI left alone still follow the overrides.
+    assert_eq!(delivered.tts.voice.as_deref(), Some("azelma"));
+}
+
+#[test]
+fn offline_voice_is_checked_for_every_backend_that_can_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let (options, cwd) = isolated(dir.path());
+    let project = cwd.join("agent-chime.json");
+    let write = |config: &str| std::fs::write(&project, config).unwrap();
+    let paths = || {
+        let report = Config::check(&options);
+        let mut paths: Vec<String> = report.issues.into_iter().map(|i| i.path).collect();
+        paths.sort();
+        paths
+    };
+
+    // A fallback reaches pocket-tts just as the primary would.
+    write(
+        r#"{
+            "tts": {
+                "backend": "command",
+                "command": { "args": ["cat"] },
+                "fallback_backends": ["pocket-tts"],
+                "allow_downloads": false,
+                "pocket_tts": { "voice": "hf://kyutai/voice.safetensors" }
+            }
+        }"#,
+    );
+    assert_eq!(paths(), ["tts.pocket_tts.voice"]);
+
+    // So do source overrides and profiles, reported where they set it.
+    write(
+        r#"{
+            "tts": {
+                "backend": "command",
+                "command": { "args": ["cat"] },
+                "allow_downloads": false,
+                "pocket_tts": { "voice": "hf://kyutai/voice.safetensors" }
+            },
+            "sources": {
+                "codex": { "backend": "pocket-tts" },
+                "claude": { "backend": "pocket-tts", "voice": "hf://kyutai/other.safetensors" }
+            },
+            "profiles": {
+                "offline": { "tts": { "backend": "pocket-tts" } },
+                "quiet": { "volume": 0.2 }
+            }
+        }"#,
+    );
+    assert_eq!(
+        paths(),
+        [
+            "profiles.offline.tts.pocket_tts.voice",
+            "sources.claude.voice",
+            "sources.codex.backend",
+        ]
+    );
+}
@@ -0,0 +1,100 @@
+use agent_chime::config::Config;
+use agent_chime::daemon::{self, Request};
+use agent_chime::events::{Event, EventType, Source};
+use std::fs::Permissions;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// A scratch runtime dir, private to this user as `runtime::ensure_dir`
+/// requires.
+fn runtime_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .unwrap()
+}
+
+fn request(dir: &Path, summary: &str) -> Request {
+    Request {
+        event: Event::with_summary(
+            EventType::AgentYield,
+            Source::Codex,
+            Some(summary.to_string()),
+        ),
+        config: Config {
+            runtime_dir: Some(dir.to_path_buf()),
+            ..Config::default()
+        },
+        backend: Some("pocket-tts".to_string()),
+        cwd: Some("/work/acme".into()),
+        pinned: serde_json::json!({ "volume": 0.25 }),
+    }
+}
+
+/// Starts a daemon in `dir` that hands each request back to the test.
+fn start_daemon(dir: &Path) -> Receiver<Request> {
+    let (delivered, received) = mpsc::channel();
+    let serve_dir = dir.to_path_buf();
+    thread::spawn(move || {
+        daemon::serve(&serve_dir, move |request: Request| {
+            delivered.send(request).unwrap();
+            Ok(())
+        })
+    });
+    for _ in 0..200 {
+        if daemon::is_running(dir) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(daemon::is_running(dir));
+    received
+}
+
+#[test]
+fn notify_delivers_itself_without_a_daemon() {
+    let dir = runtime_dir();
+    assert!(!daemon::is_running(dir.path()));
+    assert!(!daemon::forward(&request(dir.path(), "nobody home")));
+}
+
+#[test]
+fn notify_forwards_to_a_running_daemon() {
+    let dir = runtime_dir();
+    let received = start_daemon(dir.path());
+
+    assert!(daemon::forward(&request(dir.path(), "tests passed")));
+    let forwarded = received.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(forwarded.event.summary.as_deref(), Some("tests passed"));
+    assert_eq!(forwarded.backend.as_deref(), Some("pocket-tts"));
+    assert_eq!(forwarded.cwd.as_deref(), Some(Path::new("/work/acme")));
+    assert_eq!(forwarded.pinned["volume"], 0.25);
+}
+
+#[test]
+fn stale_socket_is_replaced_but_a_live_daemon_is_not() {
+    let dir = runtime_dir();
+    std::fs::write(daemon::socket_path(dir.path()), "").unwrap();
+    let _received = start_daemon(dir.path());
+
+    let err = daemon::serve(dir.path(), |_| Ok(())).unwrap_err();
+    assert!(err.to_string().contains("already running"), "{err}");
+}
+
+#[test]
+fn malformed_requests_are_rejected_before_delivery() {
+    let dir = runtime_dir();
+    let received = start_daemon(dir.path());
+
+    let mut stream = UnixStream::connect(daemon::socket_path(dir.path())).unwrap();
+    stream.write_all(b"not json\n").unwrap();
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).unwrap();
+    assert!(reply.contains(r#""ok":false"#), "{reply}");
+    assert!(received.recv_timeout(Duration::from_millis(200)).is_err());
+}
@@ -0,0 +1,145 @@
+use agent_chime::config::DebounceConfig;
+use agent_chime::debounce::{check, Verdict};
+use agent_chime::events::{Event, EventType, Source};
+use chrono::Duration;
+use serde_json::json;
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+
+/// A scratch runtime dir, private to this user as `runtime::ensure_dir`
+/// requires.
+fn runtime_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .unwrap()
+}
+
+fn event(event_type: EventType, session: &str) -> Event {
+    let mut event = Event::new(event_type, Source::Claude);
+    event.context = Some(json!({ "session_id": session }));
+    event
+}
+
+#[test]
+fn repeats_from_one_session_are_debounced() {
+    let dir = runtime_dir();
+    let config = DebounceConfig::default();
+
+    let first = event(EventType::AgentYield, "a");
+    assert_eq!(check(&first, &config, dir.path()), Verdict::Play);
+
+    let mut repeat = event(EventType::AgentYield, "a");
+    repeat.timestamp = first.timestamp + Duration::milliseconds(500);
+    assert_eq!(check(&repeat, &config, dir.path()), Verdict::Debounced);
+
+    let mut later = event(EventType::AgentYield, "a");
+    later.timestamp = first.timestamp + Duration::milliseconds(3500);
+    assert_eq!(check(&later, &config, dir.path()), Verdict::Play);
+}
+
+#[test]
+fn coalesced_types_play_once_across_sessions() {
+    let dir = runtime_dir();
+    let config = DebounceConfig::default();
+
+    let first = event(EventType::AgentYield, "a");
+    assert_eq!(check(&first, &config, dir.path()), Verdict::Play);
+
+    let mut other_session = event(EventType::AgentYield, "b");
+    other_session.timestamp = first.timestamp + Duration::milliseconds(2500);
+    assert_eq!(
+        check(&other_session, &config, dir.path()),
+        Verdict::Coalesced
+    );
+}
+
+#[test]
+fn other_types_play_once_per_session() {
+    let dir = runtime_dir();
+    let config = DebounceConfig::default();
+
+    let decision_a = event(EventType::DecisionRequired, "a");
+    let decision_b = event(EventType::DecisionRequired, "b");
+    assert_eq!(check(&decision_a, &config, dir.path()), Verdict::Play);
+    assert_eq!(check(&decision_b, &config, dir.path()), Verdict::Play);
+}
+
+#[test]
+fn parallel_agents_finishing_at_once_play_once() {
+    let dir = runtime_dir();
+    let config = DebounceConfig::default();
+
+    let burst_at = chrono::Utc::now();
+    let verdicts: Vec<Verdict> = (0..8)
+        .map(|i| {
+            let config = config.clone();
+            let dir = dir.path().to_path_buf();
+            std::thread::spawn(move || {
+                let mut event = event(EventType::AgentYield, &format!("agent-{i}"));
+                event.timestamp = burst_at;
+                check(&event, &config, &dir)
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    let played = verdicts.iter().filter(|v| **v == Verdict::Play).count();
+    assert_eq!(played, 1, "{verdicts:?}");
+}
+
+#[test]
+fn disabled_debounce_plays_everything() {
+    let dir = runtime_dir();
+    let config = DebounceConfig {
+        enabled: false,
+        ..DebounceConfig::default()
+    };
+
+    let first = event(EventType::AgentYield, "a");
+    assert_eq!(check(&first, &config, dir.path()), Verdict::Play);
+    assert_eq!(check(&first, &config, dir.path()), Verdict::Play);
+}
+
+#[test]
+fn each_type_keeps_its_own_window_when_interleaved() {
+    let dir = runtime_dir();
+    let config = DebounceConfig {
+        events: [(EventType::ErrorRetry, 10_000)].into(),
+        ..DebounceConfig::default()
+    };
+
+    let error = event(EventType::ErrorRetry, "a");
+    assert_eq!(check(&error, &config, dir.path()), Verdict::Play);
+
+    // A yield long after the default windows doesn't expire the error's.
+    let mut yield_event = event(EventType::AgentYield, "a");
+    yield_event.timestamp = error.timestamp + Duration::milliseconds(5000);
+    assert_eq!(check(&yield_event, &config, dir.path()), Verdict::Play);
+
+    let mut repeat = event(EventType::ErrorRetry, "a");
+    repeat.timestamp = error.timestamp + Duration::milliseconds(6000);
+    assert_eq!(check(&repeat, &config, dir.path()), Verdict::Debounced);
+
+    let mut yield_repeat = event(EventType::AgentYield, "a");
+    yield_repeat.timestamp = yield_event.timestamp + Duration::milliseconds(3500);
+    assert_eq!(check(&yield_repeat, &config, dir.path()), Verdict::Play);
+}
+
+#[test]
+fn events_reaching_the_lock_out_of_order_play_once() {
+    let dir = runtime_dir();
+    let config = DebounceConfig::default();
+
+    // Created 5ms apart, but the newer one got the lock first.
+    let older = event(EventType::AgentYield, "a");
+    let mut newer = event(EventType::AgentYield, "b");
+    newer.timestamp = older.timestamp + Duration::milliseconds(5);
+    assert_eq!(check(&newer, &config, dir.path()), Verdict::Play);
+    assert_eq!(check(&older, &config, dir.path()), Verdict::Coalesced);
+
+    let mut repeat = event(EventType::AgentYield, "b");
+    repeat.timestamp = newer.timestamp + Duration::milliseconds(10);
+    assert_eq!(check(&repeat, &config, dir.path()), Verdict::Debounced);
+}
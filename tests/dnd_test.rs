@@ -0,0 +1,75 @@
+use agent_chime::config::{Config, Mode};
+use agent_chime::events::{Event, EventType, Source};
+use chrono::{DateTime, TimeZone, Utc};
+
+fn load(json: &str) -> Config {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent-chime.json");
+    std::fs::write(&path, json).unwrap();
+    Config::load_from_path(&path).unwrap()
+}
+
+fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+}
+
+#[test]
+fn quiet_hours_follow_timezone_and_priority() {
+    let config = load(
+        r#"{
+            "volume": 0.8,
+            "dnd": {
+                "timezone": "Europe/Berlin",
+                "windows": [{ "days": ["mon", "tue", "wed", "thu", "fri"], "start": "22:00", "end": "07:00" }]
+            }
+        }"#,
+    );
+    let yield_event = Event::new(EventType::AgentYield, Source::Claude);
+    let decision = Event::new(EventType::DecisionRequired, Source::Claude);
+
+    // Tuesday 23:30 in Berlin, in winter (UTC+1) and summer (UTC+2).
+    for now in [utc(2026, 1, 13, 22, 30), utc(2026, 7, 14, 21, 30)] {
+        let state = config.dnd.state_at(now).unwrap();
+        assert!(state.active, "{now}");
+        assert_eq!(state.until.as_deref(), Some("Wed 07:00"));
+        assert!(config.for_quiet_hours(&yield_event, now).is_none());
+        let loud = config.for_quiet_hours(&decision, now).unwrap();
+        assert!((loud.volume - 0.4).abs() < 1e-6);
+    }
+
+    // Saturday 01:00 is still inside Friday night's window; Saturday 23:00 is not.
+    assert!(config.dnd.state_at(utc(2026, 1, 17, 0, 0)).unwrap().active);
+    let saturday_night = utc(2026, 1, 17, 22, 0);
+    assert!(!config.dnd.state_at(saturday_night).unwrap().active);
+    let normal = config
+        .for_quiet_hours(&yield_event, saturday_night)
+        .unwrap();
+    assert_eq!(normal.volume, 0.8);
+}
+
+#[test]
+fn quiet_hours_can_downgrade_to_earcon_and_are_validated() {
+    let config = load(
+        r#"{
+            "dnd": {
+                "timezone": "+05:30",
+                "windows": [{ "start": "09:00", "end": "17:00" }],
+                "breakthrough": { "normal": { "action": "earcon", "volume": 0.5 } }
+            }
+        }"#,
+    );
+    let now = utc(2026, 3, 2, 6, 0); // 11:30 local
+    let event = Event::new(EventType::AgentYield, Source::Codex);
+    let downgraded = config.for_quiet_hours(&event, now).unwrap();
+    assert_eq!(downgraded.events[&EventType::AgentYield].mode, Mode::Earcon);
+    let high = Event::new(EventType::ErrorRetry, Source::Codex);
+    assert!(config.for_quiet_hours(&high, now).is_none());
+
+    let broken = load(
+        r#"{ "dnd": { "timezone": "Mars/Olympus", "windows": [{ "start": "25:00", "end": "07:00" }] } }"#,
+    );
+    let report = broken.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"dnd.timezone"), "{paths:?}");
+    assert!(paths.contains(&"dnd.windows[0].start"), "{paths:?}");
+}
@@ -0,0 +1,146 @@
+use agent_chime::audio::earcon::{self, Earcon};
+use agent_chime::audio::tone;
+use agent_chime::config::{Config, Envelope, Note, ToneSpec, Waveform};
+use agent_chime::events::EventType;
+
+fn isolated_config(dir: &std::path::Path) -> Config {
+    Config {
+        earcons_dir: Some(dir.to_path_buf()),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn built_in_tones_cover_every_event() {
+    for event_type in EventType::ALL {
+        let spec = tone::default_spec(event_type);
+        let pcm = tone::render(&spec);
+        let ms: u64 = spec.notes.iter().map(|note| note.ms).sum();
+        assert_eq!(pcm.channels, 1);
+        assert_eq!(
+            pcm.samples.len() as u64,
+            ms * u64::from(pcm.sample_rate) / 1000
+        );
+        assert!(
+            pcm.samples.iter().any(|&s| s != 0),
+            "{event_type:?} is silent"
+        );
+    }
+}
+
+#[test]
+fn tones_follow_the_spec() {
+    let spec = ToneSpec {
+        notes: vec![Note { hz: 441.0, ms: 20 }, Note { hz: 0.0, ms: 10 }],
+        waveform: Waveform::Square,
+        envelope: Envelope {
+            attack_ms: 1,
+            release_ms: 1,
+        },
+        volume: 0.5,
+    };
+    let pcm = tone::render(&spec);
+    assert_eq!(pcm.samples.len(), 882 + 441);
+
+    // Fades in from silence, holds the square wave at half scale, and rests.
+    assert_eq!(pcm.samples[0], 0);
+    let peak = (0.5 * f32::from(i16::MAX)) as i16;
+    assert!(pcm.samples[100..400].iter().all(|&s| s.abs() == peak));
+    assert!(pcm.samples[882..].iter().all(|&s| s == 0));
+}
+
+#[test]
+fn configured_tones_then_files_then_built_ins() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = isolated_config(dir.path());
+
+    assert!(matches!(
+        earcon::resolve(EventType::ErrorRetry, &config),
+        Earcon::Tone(spec) if spec == tone::default_spec(EventType::ErrorRetry)
+    ));
+
+    std::fs::write(dir.path().join("error.wav"), b"RIFF").unwrap();
+    assert!(matches!(
+        earcon::resolve(EventType::ErrorRetry, &config),
+        Earcon::File(path) if path == dir.path().join("error.wav")
+    ));
+
+    let custom = ToneSpec {
+        notes: vec![Note { hz: 880.0, ms: 50 }],
+        waveform: Waveform::Sawtooth,
+        envelope: Envelope::default(),
+        volume: 0.2,
+    };
+    config
+        .earcon_tones
+        .insert(EventType::ErrorRetry, custom.clone());
+    assert!(matches!(
+        earcon::resolve(EventType::ErrorRetry, &config),
+        Earcon::Tone(spec) if spec == custom
+    ));
+}
+
+#[test]
+fn tone_specs_are_validated() {
+    let dir = tempfile::tempdir().unwrap();
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "earcons_dir": dir.path(),
+        "earcon_tones": {
+            "AGENT_YIELD": { "notes": [{ "hz": 440.0, "ms": 0 }], "waveform": "triangle" },
+            "ERROR_RETRY": { "notes": [], "volume": 2.0 }
+        }
+    }))
+    .unwrap();
+    let report = config.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(
+        paths.contains(&"earcon_tones.AGENT_YIELD.notes[0].ms"),
+        "{paths:?}"
+    );
+    assert!(
+        paths.contains(&"earcon_tones.ERROR_RETRY.notes"),
+        "{paths:?}"
+    );
+    assert!(
+        paths.contains(&"earcon_tones.ERROR_RETRY.volume"),
+        "{paths:?}"
+    );
+
+    // Missing WAV files are no longer a problem, a missing directory is.
+    let config = isolated_config(&dir.path().join("absent"));
+    let report = config.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"earcons_dir"), "{paths:?}");
+    assert!(!paths.iter().any(|p| p.starts_with("events.")), "{paths:?}");
+}
+
+#[test]
+fn earcon_is_queued_when_every_tts_backend_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime_dir = dir.path().join("runtime");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_agent-chime"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .args(["--set", &format!("runtime_dir={}", runtime_dir.display())])
+        .args(["--set", &format!("cache_dir={}", dir.path().display())])
+        .args(["--set", "tts.backend=missing"])
+        .args(["--set", r#"tts.fallback_backends=["nope"]"#])
+        .args(["--set", "tts.timeout_seconds=0"])
+        .args(["notify", "--source", "opencode", "--event", "AGENT_YIELD"])
+        .args(["--summary", "Tests passed."])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("all tts backends failed"),
+        "{stdout}{stderr}"
+    );
+
+    // The yield is a speech event, yet its earcon still took a turn at the
+    // speaker (whether or not a player is installed here).
+    let queue: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(runtime_dir.join("playback.json")).unwrap()).unwrap();
+    assert_eq!(queue["next_ticket"], 1, "{queue}");
+}
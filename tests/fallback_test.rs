@@ -0,0 +1,103 @@
+use agent_chime::audio::cache::AudioCache;
+use agent_chime::config::Config;
+use agent_chime::tts;
+use std::collections::HashMap;
+
+fn chained_config(cache_dir: &std::path::Path) -> Config {
+    let mut config = Config {
+        cache_dir: Some(cache_dir.to_path_buf()),
+        runtime_dir: Some(cache_dir.to_path_buf()),
+        ..Config::default()
+    };
+    config.tts.fallback_backends = vec!["nope".to_string(), "pocket-tts".to_string()];
+    config.tts.backend_timeouts = HashMap::from([("pocket-tts".to_string(), 5)]);
+    config
+}
+
+#[test]
+fn fallback_backend_answers_when_the_primary_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = chained_config(dir.path());
+
+    // Chain settings don't change what a backend sounds like, so they stay
+    // out of the cache key.
+    let plain = Config {
+        cache_dir: Some(dir.path().to_path_buf()),
+        ..Config::default()
+    };
+    let (max_size_bytes, max_entries) = plain.cache_limits();
+    let cache = AudioCache::new(dir.path().to_path_buf(), max_size_bytes, max_entries);
+    let config_json = serde_json::to_string(&plain.tts).unwrap();
+    let key = AudioCache::key("pocket-tts", "Tests passed.", &config_json);
+    cache.put(&key, b"RIFF-pocket").unwrap();
+
+    let audio = tts::synthesize("Tests passed.", &config, &Some("missing".to_string())).unwrap();
+    assert_eq!(audio, b"RIFF-pocket");
+}
+
+#[test]
+fn chain_failure_names_every_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = chained_config(dir.path());
+    config.tts.fallback_backends = vec!["nope".to_string()];
+
+    let err = tts::synthesize("Tests passed.", &config, &Some("missing".to_string())).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("all tts backends failed"), "{message}");
+    assert!(message.contains("missing: "), "{message}");
+    assert!(message.contains("nope: "), "{message}");
+
+    // A lone backend's error is passed through unchanged.
+    config.tts.fallback_backends.clear();
+    let err = tts::synthesize("Tests passed.", &config, &Some("missing".to_string())).unwrap_err();
+    assert!(
+        !err.to_string().contains("all tts backends failed"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn hung_primary_leaves_the_fallbacks_part_of_the_deadline() {
+    let dir = tempfile::tempdir().unwrap();
+    let wav_path = dir.path().join("clip.wav");
+    let (_, clip) = agent_chime::assets::FILES
+        .iter()
+        .find(|(name, _)| name.ends_with(".wav"))
+        .unwrap();
+    std::fs::write(&wav_path, clip).unwrap();
+
+    // Accepts the request and never answers.
+    let server = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/audio/speech", server.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let _connections: Vec<_> = server.incoming().collect();
+    });
+
+    // Synthesis runs in the binary's worker; nothing sets a per-backend
+    // timeout, so the 2s deadline is all there is to share.
+    let runtime_dir = dir.path().join("runtime");
+    let output_path = dir.path().join("out.wav");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_agent-chime"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .args(["--set", &format!("runtime_dir={}", runtime_dir.display())])
+        .args(["--set", &format!("cache_dir={}", dir.path().display())])
+        .args(["--set", "tts.timeout_seconds=2"])
+        .args(["--set", r#"tts.fallback_backends=["command"]"#])
+        .args(["--set", &format!("tts.http.url={url}")])
+        .args([
+            "--set",
+            &format!(r#"tts.command.args=["cat", "{}"]"#, wav_path.display()),
+        ])
+        .args(["test-tts", "--backend", "http", "--output"])
+        .arg(&output_path)
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(runtime_dir.join("worker.sock"));
+
+    // The file is written before playback, which may have no player here.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let audio = std::fs::read(&output_path).unwrap_or_else(|_| panic!("{stdout}"));
+    assert_eq!(audio, *clip);
+}
@@ -0,0 +1,294 @@
+use agent_chime::audio::wav::{self, Pcm};
+use agent_chime::config::{Config, SourceConfig};
+use agent_chime::events::Source;
+use agent_chime::tts::provider::select_backend;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+struct Captured {
+    head: String,
+    body: serde_json::Value,
+}
+
+/// Answers one request with `response` and hands back what it received.
+fn stub_server(response: Vec<u8>) -> (String, mpsc::Receiver<Captured>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://127.0.0.1:{}/v1/audio/speech",
+        listener.local_addr().unwrap().port()
+    );
+    let (sender, captured) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line == "\r\n" {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        reader.get_mut().write_all(&response).unwrap();
+        sender
+            .send(Captured {
+                head,
+                body: serde_json::from_slice(&body).unwrap(),
+            })
+            .unwrap();
+    });
+    (url, captured)
+}
+
+fn http_config(url: &str) -> Config {
+    let mut config = Config::default();
+    config.tts.backend = Some("http".to_string());
+    config.tts.voice = None;
+    config.tts.http.url = Some(url.to_string());
+    config.tts.http.model = Some("kokoro".to_string());
+    config.tts.http.voice = Some("af_sky".to_string());
+    config
+}
+
+fn response(head: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = head.replace('\n', "\r\n").into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+#[test]
+fn http_backend_posts_openai_speech_requests() {
+    let audio = wav::encode(&Pcm {
+        sample_rate: 24_000,
+        channels: 1,
+        samples: vec![0, 50, -50],
+    })
+    .unwrap();
+    let head = format!("HTTP/1.1 200 OK\nContent-Length: {}\n\n", audio.len());
+    let (url, captured) = stub_server(response(&head, &audio));
+    let mut config = http_config(&url);
+    config
+        .tts
+        .http
+        .headers
+        .insert("Authorization".to_string(), "Bearer local".to_string());
+
+    let backend = select_backend("http").unwrap();
+    assert_eq!(
+        backend
+            .synthesize("Ready.", &config.tts, Duration::ZERO)
+            .unwrap(),
+        audio
+    );
+
+    let request = captured.recv().unwrap();
+    assert!(
+        request
+            .head
+            .starts_with("POST /v1/audio/speech HTTP/1.1\r\n"),
+        "{}",
+        request.head
+    );
+    // Header names are case-insensitive; the client sends them lowercased.
+    assert!(
+        request
+            .head
+            .to_ascii_lowercase()
+            .contains("authorization: bearer local\r\n"),
+        "{}",
+        request.head
+    );
+    assert_eq!(
+        request.body,
+        serde_json::json!({
+            "model": "kokoro",
+            "input": "Ready.",
+            "voice": "af_sky",
+            "response_format": "wav",
+        })
+    );
+}
+
+#[test]
+fn http_backend_wraps_chunked_pcm_in_wav() {
+    let samples: [i16; 3] = [1, -2, 300];
+    let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mut body = format!("{:x}\r\n", 4).into_bytes();
+    body.extend_from_slice(&pcm[..4]);
+    body.extend_from_slice(format!("\r\n{:x}\r\n", 2).as_bytes());
+    body.extend_from_slice(&pcm[4..]);
+    body.extend_from_slice(b"\r\n0\r\n\r\n");
+    let (url, captured) = stub_server(response(
+        "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n",
+        &body,
+    ));
+    let mut config = http_config(&url);
+    config.tts.voice = Some("am_adam".to_string());
+    config.tts.http.response_format = Some("pcm".to_string());
+    config.tts.http.pcm_sample_rate = Some(22_050);
+
+    let backend = select_backend("http").unwrap();
+    let audio = wav::decode(
+        &backend
+            .synthesize("Ready.", &config.tts, Duration::ZERO)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(audio.sample_rate, 22_050);
+    assert_eq!(audio.channels, 1);
+    assert_eq!(audio.samples, samples);
+    assert_eq!(captured.recv().unwrap().body["voice"], "am_adam");
+}
+
+#[test]
+fn source_voice_overrides_the_server_default() {
+    let audio = wav::encode(&Pcm {
+        sample_rate: 24_000,
+        channels: 1,
+        samples: vec![0],
+    })
+    .unwrap();
+    let head = format!("HTTP/1.1 200 OK\nContent-Length: {}\n\n", audio.len());
+    let (url, captured) = stub_server(response(&head, &audio));
+    let mut config = http_config(&url);
+    config.sources.insert(
+        Source::Codex,
+        SourceConfig {
+            voice: Some("bm_george".to_string()),
+            ..SourceConfig::default()
+        },
+    );
+
+    let backend = select_backend("http").unwrap();
+    let codex = config.for_source(Source::Codex);
+    backend
+        .synthesize("Ready.", &codex.tts, Duration::ZERO)
+        .unwrap();
+    assert_eq!(captured.recv().unwrap().body["voice"], "bm_george");
+}
+
+#[test]
+fn http_backend_reports_server_errors() {
+    let (url, _captured) = stub_server(response(
+        "HTTP/1.1 400 Bad Request\nContent-Length: 17\n\n",
+        b"voice not found\r\n",
+    ));
+    let backend = select_backend("http").unwrap();
+    let err = backend
+        .synthesize("Ready.", &http_config(&url).tts, Duration::ZERO)
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("400"), "{message}");
+    assert!(message.contains("voice not found"), "{message}");
+}
+
+#[test]
+fn http_backend_skips_interim_responses() {
+    let audio = wav::encode(&Pcm {
+        sample_rate: 24_000,
+        channels: 1,
+        samples: vec![7],
+    })
+    .unwrap();
+    let head = format!(
+        "HTTP/1.1 100 Continue\n\nHTTP/1.1 200 OK\nContent-Length: {}\n\n",
+        audio.len()
+    );
+    let (url, _captured) = stub_server(response(&head, &audio));
+    let backend = select_backend("http").unwrap();
+    assert_eq!(
+        backend
+            .synthesize("Ready.", &http_config(&url).tts, Duration::ZERO)
+            .unwrap(),
+        audio
+    );
+}
+
+#[test]
+fn http_backend_config_is_checked() {
+    let mut config = http_config("https://api.example.com/v1/audio/speech");
+    assert!(config.validation_report().issues.is_empty());
+
+    config.tts.http.url = Some("ftp://api.example.com/speech".to_string());
+    config.tts.http.response_format = Some("mp3".to_string());
+    config
+        .tts
+        .http
+        .headers
+        .insert("X-Token".to_string(), "abc\r\nX-Admin: yes".to_string());
+    let report = config.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"tts.http.url"), "{paths:?}");
+    assert!(paths.contains(&"tts.http.response_format"), "{paths:?}");
+    assert!(paths.contains(&"tts.http.headers.X-Token"), "{paths:?}");
+
+    config.tts.http.url = None;
+    let report = config.validation_report();
+    assert!(report.issues.iter().any(|i| i.path == "tts.http.url"));
+}
+
+#[test]
+fn http_backend_refuses_what_it_cannot_send_or_play() {
+    let backend = select_backend("http").unwrap();
+    let mut config = http_config("http://127.0.0.1:9/v1/audio/speech");
+    config.tts.http.response_format = Some("opus".to_string());
+    let err = backend
+        .synthesize("Ready.", &config.tts, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("opus is compressed"), "{err:#}");
+
+    config.tts.http.response_format = None;
+    config
+        .tts
+        .http
+        .headers
+        .insert("X-Token".to_string(), "abc\r\nX-Admin: yes".to_string());
+    let err = backend
+        .synthesize("Ready.", &config.tts, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("line break"), "{err:#}");
+}
+
+#[test]
+fn http_backend_gives_up_at_the_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/audio/speech", listener.local_addr().unwrap());
+    // Accepts the connection but never answers.
+    let hold = thread::spawn(move || listener.accept().map(|(stream, _)| stream));
+    let mut config = http_config(&url);
+    config.tts.timeout_seconds = 1;
+
+    let backend = select_backend("http").unwrap();
+    let started = std::time::Instant::now();
+    assert!(backend
+        .synthesize("Ready.", &config.tts, Duration::ZERO)
+        .is_err());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    drop(hold.join());
+}
+
+#[test]
+fn http_backend_gives_up_within_the_chain_budget() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/audio/speech", listener.local_addr().unwrap());
+    let hold = thread::spawn(move || listener.accept().map(|(stream, _)| stream));
+    let mut config = http_config(&url);
+    config.tts.timeout_seconds = 30;
+
+    let backend = select_backend("http").unwrap();
+    let started = std::time::Instant::now();
+    let budget = Duration::from_millis(300);
+    assert!(backend.synthesize("Ready.", &config.tts, budget).is_err());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    drop(hold.join());
+}
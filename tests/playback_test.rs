@@ -0,0 +1,101 @@
+use agent_chime::audio::renderer::{self, Playback, PlaybackGuard};
+use agent_chime::events::Priority;
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A scratch runtime dir, private to this user as `runtime::ensure_dir`
+/// requires.
+fn runtime_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .unwrap()
+}
+
+const LONG: Duration = Duration::from_secs(10);
+
+fn acquire(dir: &Path, priority: Priority, max_wait: Duration) -> Option<PlaybackGuard> {
+    renderer::acquire(&Playback {
+        volume: 1.0,
+        priority,
+        max_wait,
+        preempt: false,
+        runtime_dir: dir.to_path_buf(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn waiters_from_dead_processes_are_skipped() {
+    let dir = runtime_dir();
+    std::fs::write(
+        dir.path().join("playback.json"),
+        r#"{"next_ticket":7,"waiters":[{"ticket":7,"priority":"high","seen":0}]}"#,
+    )
+    .unwrap();
+    assert!(acquire(dir.path(), Priority::Normal, LONG).is_some());
+}
+
+#[test]
+fn equal_or_lower_priority_is_dropped_after_max_wait() {
+    let dir = runtime_dir();
+    let _playing = acquire(dir.path(), Priority::Normal, LONG).unwrap();
+
+    let started = Instant::now();
+    assert!(acquire(dir.path(), Priority::Low, Duration::from_millis(100)).is_none());
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert!(acquire(dir.path(), Priority::Normal, Duration::from_millis(50)).is_none());
+}
+
+#[test]
+fn queued_clips_play_by_priority_then_arrival() {
+    let dir = runtime_dir();
+    let playing = acquire(dir.path(), Priority::Normal, LONG).unwrap();
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut handles = Vec::new();
+    for (name, priority) in [
+        ("normal-1", Priority::Normal),
+        ("normal-2", Priority::Normal),
+        ("high", Priority::High),
+    ] {
+        let order = Arc::clone(&order);
+        let dir = dir.path().to_path_buf();
+        handles.push(thread::spawn(move || {
+            let guard = acquire(&dir, priority, LONG).expect(name);
+            order.lock().unwrap().push(name);
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        }));
+        thread::sleep(Duration::from_millis(100));
+    }
+    drop(playing);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), ["high", "normal-1", "normal-2"]);
+}
+
+#[test]
+fn higher_priority_is_dropped_after_max_wait_too() {
+    let dir = runtime_dir();
+    let _playing = acquire(dir.path(), Priority::Low, LONG).unwrap();
+
+    let started = Instant::now();
+    assert!(acquire(dir.path(), Priority::High, Duration::from_millis(100)).is_none());
+    assert!(started.elapsed() < LONG);
+}
+
+#[test]
+fn clip_is_dropped_when_the_lock_is_held_without_a_holder() {
+    let dir = runtime_dir();
+    // A process that took the lock but never recorded itself.
+    let wedged = std::fs::File::create(dir.path().join("playback.lock")).unwrap();
+    wedged.lock().unwrap();
+
+    assert!(acquire(dir.path(), Priority::High, Duration::from_millis(100)).is_none());
+}
@@ -0,0 +1,83 @@
+use agent_chime::audio::renderer::{acquire, Playback};
+use agent_chime::events::Priority;
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A scratch runtime dir, private to this user as `runtime::ensure_dir`
+/// requires.
+fn runtime_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .unwrap()
+}
+
+const LONG: Duration = Duration::from_secs(10);
+
+fn playback(dir: &Path, priority: Priority, max_wait: Duration) -> Playback {
+    Playback {
+        volume: 1.0,
+        priority,
+        max_wait,
+        preempt: true,
+        runtime_dir: dir.to_path_buf(),
+    }
+}
+
+#[test]
+fn higher_priority_stops_the_playing_clip() {
+    let dir = runtime_dir();
+    let guard = acquire(&playback(dir.path(), Priority::Normal, LONG))
+        .unwrap()
+        .unwrap();
+    let player = Command::new("sleep").arg("30").spawn().unwrap();
+
+    let started = Instant::now();
+    let urgent = playback(dir.path(), Priority::High, LONG);
+    let urgent = thread::spawn(move || acquire(&urgent).unwrap().is_some());
+
+    // The player is killed well before it would have finished.
+    assert!(guard.wait(player).unwrap().is_none());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    drop(guard);
+    assert!(urgent.join().unwrap());
+}
+
+#[test]
+fn equal_priority_waits_instead_of_preempting() {
+    let dir = runtime_dir();
+    let guard = acquire(&playback(dir.path(), Priority::Normal, LONG))
+        .unwrap()
+        .unwrap();
+
+    assert!(acquire(&playback(
+        dir.path(),
+        Priority::Normal,
+        Duration::from_millis(200)
+    ))
+    .unwrap()
+    .is_none());
+    assert!(!guard.preempted());
+
+    let player = Command::new("true").spawn().unwrap();
+    assert!(guard.wait(player).unwrap().is_some_and(|s| s.success()));
+}
+
+#[test]
+fn clip_preempted_before_its_player_starts_is_flagged() {
+    let dir = runtime_dir();
+    let low = acquire(&playback(dir.path(), Priority::Low, LONG))
+        .unwrap()
+        .unwrap();
+    let urgent = playback(dir.path(), Priority::High, LONG);
+    let urgent = thread::spawn(move || acquire(&urgent).unwrap().is_some());
+    while !low.preempted() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    drop(low);
+    assert!(urgent.join().unwrap());
+}
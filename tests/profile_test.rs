@@ -0,0 +1,97 @@
+use agent_chime::config::{
+    save_active_profile, Config, Layer, LoadOptions, Mode, ProfileSource, PROFILE_ENV,
+};
+use agent_chime::events::EventType;
+use tempfile::TempDir;
+
+/// A project config with two profiles, and options that see nothing else:
+/// no system or user config, no saved profile and an empty environment.
+fn fixture() -> (TempDir, LoadOptions) {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    std::fs::write(
+        repo.join("agent-chime.json"),
+        r#"{
+            "volume": 0.7,
+            "profiles": {
+                "office": { "volume": 0.3, "events": { "AGENT_YIELD": { "mode": "earcon" } } },
+                "presenting": { "volume": 0.0 }
+            }
+        }"#,
+    )
+    .unwrap();
+    let options = LoadOptions {
+        system_dir: Some(dir.path().join("system")),
+        user_dir: Some(dir.path().join("user")),
+        state_dir: Some(dir.path().join("state")),
+        cwd: Some(repo),
+        env: Some(Vec::new()),
+        ..Default::default()
+    };
+    (dir, options)
+}
+
+fn with_env_profile(options: &LoadOptions, name: &str) -> LoadOptions {
+    LoadOptions {
+        env: Some(vec![(PROFILE_ENV.to_string(), name.to_string())]),
+        ..options.clone()
+    }
+}
+
+#[test]
+fn saved_profile_overlays_the_file_layers() {
+    let (_dir, options) = fixture();
+    let (config, resolution) = Config::resolve(&options).unwrap();
+    assert_eq!(config.volume, 0.7);
+    assert_eq!(resolution.profile, None);
+
+    save_active_profile(&options, Some("office")).unwrap();
+    let (config, resolution) = Config::resolve(&options).unwrap();
+    assert_eq!(config.volume, 0.3);
+    assert_eq!(config.events[&EventType::AgentYield].mode, Mode::Earcon);
+    assert_eq!(
+        config.events[&EventType::AgentYield].template.as_deref(),
+        Some("Ready.")
+    );
+    assert_eq!(resolution.layer_for("volume"), Layer::Profile);
+    assert_eq!(resolution.profile.unwrap().source, ProfileSource::State);
+
+    save_active_profile(&options, None).unwrap();
+    assert_eq!(Config::load_with(&options).unwrap().volume, 0.7);
+}
+
+#[test]
+fn cli_beats_env_beats_the_saved_profile() {
+    let (_dir, options) = fixture();
+    save_active_profile(&options, Some("office")).unwrap();
+
+    let env = with_env_profile(&options, "presenting");
+    let (config, resolution) = Config::resolve(&env).unwrap();
+    assert_eq!(config.volume, 0.0);
+    assert_eq!(resolution.profile.unwrap().source, ProfileSource::Env);
+
+    let cli = LoadOptions {
+        profile: Some("office".to_string()),
+        ..env
+    };
+    let (config, resolution) = Config::resolve(&cli).unwrap();
+    assert_eq!(config.volume, 0.3);
+    assert_eq!(resolution.profile.unwrap().source, ProfileSource::Cli);
+}
+
+#[test]
+fn unknown_profile_fails_unless_it_was_only_saved() {
+    let (_dir, options) = fixture();
+    let env = with_env_profile(&options, "missing");
+    assert!(Config::load_with(&env).is_err());
+    let report = Config::check(&env);
+    assert!(
+        report.issues.iter().any(|i| i.path == "profile"),
+        "{report}"
+    );
+
+    // A saved profile that was since deleted is ignored rather than fatal.
+    save_active_profile(&options, Some("gone")).unwrap();
+    assert_eq!(Config::load_with(&options).unwrap().volume, 0.7);
+}
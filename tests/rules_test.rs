@@ -0,0 +1,101 @@
+use agent_chime::config::{Config, Mode};
+use agent_chime::events::{Event, EventType, Priority, Source};
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use std::path::Path;
+
+fn load(json: &str) -> Config {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("agent-chime.json");
+    std::fs::write(&path, json).unwrap();
+    Config::load_from_path(&path).unwrap()
+}
+
+#[test]
+fn rules_apply_in_order_until_stop_or_suppress() {
+    let config = load(
+        r#"{
+            "volume": 0.8,
+            "rules": [
+                {
+                    "name": "quiet bash",
+                    "when": { "source": ["claude"], "context": { "/tool_name": "Bash" } },
+                    "then": { "mode": "earcon", "volume": 0.2, "priority": "low" }
+                },
+                {
+                    "when": { "summary": "tests? passed", "event": ["AGENT_YIELD"] },
+                    "then": { "template": "Green: {summary}", "voice": "azelma", "stop": true }
+                },
+                { "when": { "priority": ["low"] }, "then": { "suppress": true } },
+                { "when": { "source": ["codex"] }, "then": { "instruct": "whisper" } }
+            ]
+        }"#,
+    );
+    let now = Utc::now();
+
+    let mut passed = Event::with_summary(
+        EventType::AgentYield,
+        Source::Codex,
+        Some("All TESTS PASSED".to_string()),
+    );
+    let result = config.apply_rules(&mut passed, None, now).unwrap();
+    let yield_config = &result.events[&EventType::AgentYield];
+    assert_eq!(yield_config.template.as_deref(), Some("Green: {summary}"));
+    assert_eq!(result.tts.voice.as_deref(), Some("azelma"));
+    // `stop` skipped the codex rule.
+    assert_eq!(result.tts.instruct, None);
+
+    let mut bash = Event::new(EventType::DecisionRequired, Source::Claude);
+    bash.context = Some(json!({ "tool_name": "Bash" }));
+    assert!(config.apply_rules(&mut bash, None, now).is_none());
+    assert_eq!(bash.priority, Priority::Low);
+
+    let mut other = Event::new(EventType::ErrorRetry, Source::Codex);
+    let result = config.apply_rules(&mut other, None, now).unwrap();
+    assert_eq!(result.tts.instruct.as_deref(), Some("whisper"));
+    assert_eq!(result.volume, 0.8);
+    assert_eq!(result.events[&EventType::ErrorRetry].mode, Mode::Earcon);
+}
+
+#[test]
+fn rule_conditions_cover_path_and_time() {
+    let config = load(
+        r#"{
+            "rules": [
+                { "when": { "path": "/work/**" }, "then": { "volume": 0.1 } },
+                {
+                    "when": { "time": { "start": "12:00", "end": "13:00" }, "timezone": "UTC" },
+                    "then": { "mode": "silent" }
+                }
+            ]
+        }"#,
+    );
+
+    let mut event = Event::new(EventType::AgentYield, Source::OpenCode);
+    let lunch = Utc.with_ymd_and_hms(2026, 5, 4, 12, 30, 0).unwrap();
+    let cwd = Path::new("/work/acme/api");
+    let result = config.apply_rules(&mut event, Some(cwd), lunch).unwrap();
+    assert_eq!(result.volume, 0.1);
+    assert_eq!(result.events[&EventType::AgentYield].mode, Mode::Silent);
+
+    // Without a known cwd, `path` conditions don't match.
+    let result = config.apply_rules(&mut event, None, lunch).unwrap();
+    assert_eq!(result.volume, 0.8);
+
+    let evening = Utc.with_ymd_and_hms(2026, 5, 4, 19, 0, 0).unwrap();
+    let other = Path::new("/home/me/other");
+    let result = config
+        .apply_rules(&mut event, Some(other), evening)
+        .unwrap();
+    assert_eq!(result.volume, 0.8);
+    assert_eq!(result.events[&EventType::AgentYield].mode, Mode::Tts);
+
+    let broken = load(
+        r#"{ "rules": [{ "when": { "summary": "(", "context": { "tool_name": 1 } }, "then": { "volume": 2 } }] }"#,
+    );
+    let report = broken.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"rules[0].when.summary"), "{paths:?}");
+    assert!(paths.contains(&"rules[0].when.context"), "{paths:?}");
+    assert!(paths.contains(&"rules[0].then.volume"), "{paths:?}");
+}
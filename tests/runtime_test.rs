@@ -0,0 +1,32 @@
+use agent_chime::runtime;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn runtime_dir_is_created_private() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime_dir = dir.path().join("agent-chime");
+
+    runtime::open(&runtime_dir, "queue.json").unwrap();
+    let mode = std::fs::metadata(&runtime_dir)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o700);
+}
+
+#[test]
+fn runtime_dir_open_to_others_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime_dir = dir.path().join("agent-chime");
+    std::fs::create_dir(&runtime_dir).unwrap();
+    std::fs::set_permissions(&runtime_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+    let err = runtime::open(&runtime_dir, "queue.json").unwrap_err();
+    assert!(err.to_string().contains("open to other users"), "{err:#}");
+    assert!(!runtime_dir.join("queue.json").exists());
+
+    // A symlink planted in its place isn't followed either.
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(dir.path(), &link).unwrap();
+    assert!(runtime::ensure_dir(&link).is_err());
+}
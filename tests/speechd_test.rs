@@ -0,0 +1,173 @@
+use agent_chime::config::{Config, SourceConfig};
+use agent_chime::events::{Priority, Source};
+use agent_chime::tts::{self, provider::select_backend};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Serves one SSIP session, refusing `SET self RATE` when asked to, and hands
+/// back every line it received.
+fn fake_speechd(socket: &Path, refuse_rate: bool) -> mpsc::Receiver<Vec<String>> {
+    let listener = UnixListener::bind(socket).unwrap();
+    let (sender, received) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut lines = Vec::new();
+        let mut speaking = false;
+        for line in BufReader::new(stream).lines() {
+            let line = line.unwrap().trim_end_matches('\r').to_string();
+            lines.push(line.clone());
+            let reply = if speaking {
+                if line != "." {
+                    continue;
+                }
+                speaking = false;
+                "225-21\r\n225 OK MESSAGE QUEUED\r\n"
+            } else if line == "SPEAK" {
+                speaking = true;
+                "230 OK RECEIVING DATA\r\n"
+            } else if line == "QUIT" {
+                writer.write_all(b"231 HAPPY HACKING\r\n").unwrap();
+                break;
+            } else if refuse_rate && line.starts_with("SET self RATE") {
+                "410 ERR PARAMETER INVALID\r\n"
+            } else {
+                "200 OK\r\n"
+            };
+            writer.write_all(reply.as_bytes()).unwrap();
+        }
+        let _ = sender.send(lines);
+    });
+    received
+}
+
+fn speechd_config(dir: &Path) -> Config {
+    let mut config = Config {
+        cache_dir: Some(dir.join("cache")),
+        ..Config::default()
+    };
+    config.tts.backend = Some("speech-dispatcher".to_string());
+    config.tts.speech_dispatcher.socket = Some(dir.join("speechd.sock"));
+    config.tts.speech_dispatcher.voice = Some("Alan".to_string());
+    config.tts.speech_dispatcher.rate = Some(20);
+    config.tts.speech_dispatcher.volume = Some(-10);
+    config
+}
+
+#[test]
+fn speech_dispatcher_speaks_over_ssip_without_cache_or_playback() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = speechd_config(dir.path());
+    // An effective volume of 0.5 (from quiet hours, say) halves its level.
+    config.volume = 0.5;
+    let received = fake_speechd(&dir.path().join("speechd.sock"), false);
+
+    tts::synthesize_and_play(
+        ".env changed.<break 300ms/> Tests passed.",
+        &config,
+        &None,
+        Priority::Normal,
+    )
+    .unwrap();
+
+    let lines = received.recv().unwrap();
+    assert_eq!(
+        lines,
+        [
+            "SET self CLIENT_NAME user:agent-chime:notify",
+            "SET self PRIORITY message",
+            "SET self SYNTHESIS_VOICE Alan",
+            "SET self RATE 20",
+            "SET self VOLUME -55",
+            "SPEAK",
+            "..env changed. Tests passed.",
+            ".",
+            "QUIT",
+        ]
+    );
+    assert!(!dir.path().join("cache").exists());
+}
+
+#[test]
+fn source_voice_and_event_priority_reach_the_daemon() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = speechd_config(dir.path());
+    config.sources.insert(
+        Source::Codex,
+        SourceConfig {
+            voice: Some("Joan".to_string()),
+            ..SourceConfig::default()
+        },
+    );
+    let received = fake_speechd(&dir.path().join("speechd.sock"), false);
+
+    let codex = config.for_source(Source::Codex);
+    tts::synthesize_and_play("Approve?", &codex, &None, Priority::High).unwrap();
+
+    let lines = received.recv().unwrap();
+    assert_eq!(lines[1], "SET self PRIORITY important");
+    assert_eq!(lines[2], "SET self SYNTHESIS_VOICE Joan");
+}
+
+#[test]
+fn speech_dispatcher_errors_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = speechd_config(dir.path());
+    let backend = select_backend("speech-dispatcher").unwrap();
+
+    let err = backend
+        .speak("Ready.", &config, Priority::Normal, Duration::ZERO)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("connect to speech-dispatcher"),
+        "{err:#}"
+    );
+
+    let _received = fake_speechd(&dir.path().join("speechd.sock"), true);
+    let err = backend
+        .speak("Ready.", &config, Priority::Normal, Duration::ZERO)
+        .unwrap_err();
+    assert!(err.to_string().contains("410"), "{err:#}");
+
+    // A daemon that never answers is given up on at the deadline.
+    std::fs::remove_file(dir.path().join("speechd.sock")).unwrap();
+    let _silent = UnixListener::bind(dir.path().join("speechd.sock")).unwrap();
+    let started = Instant::now();
+    let err = backend
+        .speak(
+            "Ready.",
+            &config,
+            Priority::Normal,
+            Duration::from_millis(300),
+        )
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("timed out after 300ms")
+            || format!("{err:#}").contains("read from speech-dispatcher"),
+        "{err:#}"
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // There is never audio to hand back.
+    assert!(backend
+        .synthesize("Ready.", &config.tts, Duration::ZERO)
+        .is_err());
+}
+
+#[test]
+fn speech_dispatcher_rate_and_volume_are_checked() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = speechd_config(dir.path());
+    config.tts.speech_dispatcher.rate = Some(150);
+    let report = config.validation_report();
+    let paths: Vec<&str> = report.issues.iter().map(|i| i.path.as_str()).collect();
+    assert!(paths.contains(&"tts.speech_dispatcher.rate"), "{paths:?}");
+    assert!(
+        !paths.contains(&"tts.speech_dispatcher.volume"),
+        "{paths:?}"
+    );
+}
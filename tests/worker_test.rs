@@ -0,0 +1,185 @@
+use agent_chime::assets;
+use agent_chime::audio::wav::{self, Pcm};
+use agent_chime::config::Config;
+use agent_chime::tts::worker::Worker;
+use std::fs::Permissions;
+use std::io::{Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A scratch runtime dir, private to this user as `runtime::ensure_dir`
+/// requires.
+fn runtime_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .permissions(Permissions::from_mode(0o700))
+        .tempdir()
+        .unwrap()
+}
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn worker(dir: &Path) -> Worker {
+    Worker::new(Path::new(env!("CARGO_BIN_EXE_agent-chime")), dir)
+}
+
+/// A config whose `command` backend runs `args`.
+fn command_config(args: &[&str]) -> Config {
+    let mut config = Config::default();
+    config.tts.command.args = args.iter().map(|arg| arg.to_string()).collect();
+    config
+}
+
+fn socket_id(worker: &Worker) -> u64 {
+    std::fs::metadata(worker.socket_path()).unwrap().ino()
+}
+
+fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+    let started = Instant::now();
+    while started.elapsed() < Duration::from_secs(5) {
+        if done() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(25));
+    }
+    false
+}
+
+/// Serves each canned reply to one connection, like a worker would.
+fn fake_worker(dir: &Path, replies: Vec<Vec<u8>>) -> thread::JoinHandle<()> {
+    let listener = UnixListener::bind(dir.join("worker.sock")).unwrap();
+    thread::spawn(move || {
+        for reply in replies {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut len = [0u8; 4];
+            stream.read_exact(&mut len).unwrap();
+            let mut request = vec![0; u32::from_be_bytes(len) as usize];
+            stream.read_exact(&mut request).unwrap();
+            stream.write_all(&reply).unwrap();
+        }
+    })
+}
+
+fn frame(status: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = ((payload.len() + 1) as u32).to_be_bytes().to_vec();
+    frame.push(status);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[test]
+fn one_worker_serves_separate_callers() {
+    let dir = runtime_dir();
+    let wav_path = dir.path().join("clip.wav");
+    let (_, clip) = assets::FILES
+        .iter()
+        .find(|(name, _)| name.ends_with(".wav"))
+        .unwrap();
+    std::fs::write(&wav_path, clip).unwrap();
+    let config = command_config(&["cat", wav_path.to_str().unwrap()]);
+
+    // Each `Worker` stands in for a separate `notify` process.
+    let audio = worker(dir.path())
+        .synthesize("hello", &config, "command", TIMEOUT)
+        .unwrap();
+    assert_eq!(audio, *clip);
+    let started = socket_id(&worker(dir.path()));
+
+    // Backend errors come back as responses and leave the worker running.
+    let err = worker(dir.path())
+        .synthesize("hello", &config, "nope", TIMEOUT)
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown backend: nope"), "{err:#}");
+    let err = worker(dir.path())
+        .stream("hello", &config, "nope", TIMEOUT, &mut |_| {})
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown backend: nope"), "{err:#}");
+
+    worker(dir.path())
+        .synthesize("hello", &config, "command", TIMEOUT)
+        .unwrap();
+    assert_eq!(socket_id(&worker(dir.path())), started);
+    std::fs::remove_file(worker(dir.path()).socket_path()).unwrap();
+}
+
+#[test]
+fn worker_that_overruns_exits_and_is_replaced() {
+    let dir = runtime_dir();
+    let hung = command_config(&["sleep", "30"]);
+    let err = worker(dir.path())
+        .synthesize("hello", &hung, "command", Duration::from_millis(500))
+        .unwrap_err();
+    assert!(err.to_string().contains("timed out after 500ms"), "{err:#}");
+    assert!(wait_until(|| !worker(dir.path()).is_running()));
+
+    let err = worker(dir.path())
+        .synthesize("hello", &hung, "nope", TIMEOUT)
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown backend"), "{err:#}");
+    assert!(worker(dir.path()).is_running());
+    std::fs::remove_file(worker(dir.path()).socket_path()).unwrap();
+}
+
+#[test]
+fn worker_streams_chunks_before_the_final_frame() {
+    let dir = runtime_dir();
+    let chunk = |samples: Vec<i16>| Pcm {
+        sample_rate: 24_000,
+        channels: 1,
+        samples,
+    };
+    let mut reply = frame(2, &wav::encode(&chunk(vec![1, 2, 3])).unwrap());
+    reply.extend(frame(2, &wav::encode(&chunk(vec![4, 5])).unwrap()));
+    reply.extend(frame(0, b""));
+    let server = fake_worker(dir.path(), vec![reply, frame(1, b"unknown backend: nope")]);
+
+    let mut chunks = Vec::new();
+    worker(dir.path())
+        .stream(
+            "hello",
+            &Config::default(),
+            "pocket-tts",
+            TIMEOUT,
+            &mut |pcm| chunks.push(pcm.samples),
+        )
+        .unwrap();
+    assert_eq!(chunks, [vec![1, 2, 3], vec![4, 5]]);
+
+    let err = worker(dir.path())
+        .stream("again", &Config::default(), "nope", TIMEOUT, &mut |_| {})
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown backend"), "{err:#}");
+    server.join().unwrap();
+}
+
+#[test]
+fn worker_that_hangs_up_is_retried_once() {
+    let dir = runtime_dir();
+    let replies = vec![Vec::new(), frame(0, b"audio"), Vec::new(), Vec::new()];
+    let server = fake_worker(dir.path(), replies);
+    let config = Config::default();
+
+    let audio = worker(dir.path())
+        .synthesize("hello", &config, "pocket-tts", TIMEOUT)
+        .unwrap();
+    assert_eq!(audio, b"audio");
+    let err = worker(dir.path())
+        .synthesize("hello", &config, "pocket-tts", TIMEOUT)
+        .unwrap_err();
+    assert!(err.to_string().contains("tts worker exited"), "{err:#}");
+    server.join().unwrap();
+}
+
+#[test]
+fn worker_that_garbles_a_reply_is_an_error() {
+    let dir = runtime_dir();
+    let server = fake_worker(dir.path(), vec![frame(9, b"")]);
+
+    let err = worker(dir.path())
+        .synthesize("hello", &Config::default(), "pocket-tts", TIMEOUT)
+        .unwrap_err();
+    assert!(err.to_string().contains("malformed"), "{err:#}");
+    server.join().unwrap();
+}